@@ -1,9 +1,28 @@
+use bevy::asset::{AssetLoader, LoadContext, io::Reader};
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
+use strum_macros::{EnumIter, EnumString, IntoStaticStr};
 
-#[derive(EnumIter, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use crate::hex::HexDirection;
+
+/// Tile types. The variant name is the stable id used by the catalog, map files, shared
+/// map links and `set_tile`.
+#[derive(
+    EnumIter,
+    EnumString,
+    IntoStaticStr,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+)]
 pub enum AssetTag {
+    /// No tile. Selecting it erases hexes; it is never stored in the map.
     None,
     Outline,
     Blank,
@@ -60,145 +79,339 @@ pub enum AssetTag {
     SwampStill,
     SwanpWaves,
     WetlandsDamp,
+    CoastBigE,
+    CoastBigNE,
+    CoastBigNW,
+    CoastBigW,
+    CoastBigSW,
+    CoastBigSE,
+    CoastMediumE,
+    CoastMediumNE,
+    CoastMediumNW,
+    CoastMediumW,
+    CoastMediumSW,
+    CoastMediumSE,
+    CoastSmallE,
+    CoastSmallNE,
+    CoastSmallNW,
+    CoastSmallW,
+    CoastSmallSW,
+    CoastSmallSE,
     // etc...
 }
 
-pub fn all_asset_tags() -> Vec<AssetTag> {
-    AssetTag::iter().collect()
+/// Climate a tile is drawn for, named by the last word of its tag (`HillsLush`,
+/// `HillsSnowy`). Parsed case-insensitively from the UI.
+#[derive(EnumIter, EnumString, IntoStaticStr, Debug, Clone, Copy, PartialEq, Eq)]
+#[strum(ascii_case_insensitive)]
+pub enum Biome {
+    Lush,
+    Damp,
+    Desert,
+    Rocky,
+    Snowy,
+}
+
+/// Width of the beach on a coast tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoastSize {
+    Small,
+    Medium,
+    Big,
 }
 
+/// Every coast tag with the size and the direction its water side faces.
+const COAST_TAGS: [(AssetTag, CoastSize, HexDirection); 18] = [
+    (AssetTag::CoastBigE, CoastSize::Big, HexDirection::East),
+    (
+        AssetTag::CoastBigNE,
+        CoastSize::Big,
+        HexDirection::NorthEast,
+    ),
+    (
+        AssetTag::CoastBigNW,
+        CoastSize::Big,
+        HexDirection::NorthWest,
+    ),
+    (AssetTag::CoastBigW, CoastSize::Big, HexDirection::West),
+    (
+        AssetTag::CoastBigSW,
+        CoastSize::Big,
+        HexDirection::SouthWest,
+    ),
+    (
+        AssetTag::CoastBigSE,
+        CoastSize::Big,
+        HexDirection::SouthEast,
+    ),
+    (
+        AssetTag::CoastMediumE,
+        CoastSize::Medium,
+        HexDirection::East,
+    ),
+    (
+        AssetTag::CoastMediumNE,
+        CoastSize::Medium,
+        HexDirection::NorthEast,
+    ),
+    (
+        AssetTag::CoastMediumNW,
+        CoastSize::Medium,
+        HexDirection::NorthWest,
+    ),
+    (
+        AssetTag::CoastMediumW,
+        CoastSize::Medium,
+        HexDirection::West,
+    ),
+    (
+        AssetTag::CoastMediumSW,
+        CoastSize::Medium,
+        HexDirection::SouthWest,
+    ),
+    (
+        AssetTag::CoastMediumSE,
+        CoastSize::Medium,
+        HexDirection::SouthEast,
+    ),
+    (AssetTag::CoastSmallE, CoastSize::Small, HexDirection::East),
+    (
+        AssetTag::CoastSmallNE,
+        CoastSize::Small,
+        HexDirection::NorthEast,
+    ),
+    (
+        AssetTag::CoastSmallNW,
+        CoastSize::Small,
+        HexDirection::NorthWest,
+    ),
+    (AssetTag::CoastSmallW, CoastSize::Small, HexDirection::West),
+    (
+        AssetTag::CoastSmallSW,
+        CoastSize::Small,
+        HexDirection::SouthWest,
+    ),
+    (
+        AssetTag::CoastSmallSE,
+        CoastSize::Small,
+        HexDirection::SouthEast,
+    ),
+];
+
 impl AssetTag {
-    pub fn from_str(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
-            "outline" => AssetTag::Outline,
-            "blank (white)" => AssetTag::Blank,
-            "lush (green)" => AssetTag::BaseLush,
-            "ocean (blue)" => AssetTag::BaseOcean,
-            "rocky (gray)" => AssetTag::BaseRocky,
-            "snowy (off-white)" => AssetTag::BaseSnowy,
-            "forest conifer, lush" => AssetTag::ForestConiferLush,
-            "forest conifer, snowy" => AssetTag::ForestConiferSnowy,
-            "forest deciduous, lush" => AssetTag::ForestDeciduousLush,
-            "forest mixed, lush" => AssetTag::ForestMixedLush,
-            "hills, desert" => AssetTag::HillsDesert,
-            "hills, lush" => AssetTag::HillsLush,
-            "hills, snowy" => AssetTag::HillsSnowy,
-            "mountain volcano, lush" => AssetTag::MountainVolcanoLush,
-            "mountain volcano, rocky" => AssetTag::MountainVolcanoRocky,
-            "mountain volcano, snowy" => AssetTag::MountainVolcanoSnowy,
-            "mountain foothills, lush" => AssetTag::MountainFoothillsLush,
-            "mountain foothills, rocky" => AssetTag::MountainFoothillsRocky,
-            "mountain foothills, snowy" => AssetTag::MountainFoothillsSnowy,
-            "mountain low, lush" => AssetTag::MountainLowLush,
-            "mountain low, rocky" => AssetTag::MountainLowRocky,
-            "mountain low, snowy" => AssetTag::MountainLowSnowy,
-            "mountain medium, lush" => AssetTag::MountainMediumLush,
-            "mountain medium, rocky" => AssetTag::MountainMediumRocky,
-            "mountain medium, snowy" => AssetTag::MountainMediumSnowy,
-            "mountain peak, lush" => AssetTag::MountainPeakLush,
-            "mountain peak, rocky" => AssetTag::MountainPeakRocky,
-            "mountain peak, snowy" => AssetTag::MountainPeakSnowy,
-            "plains, damp" => AssetTag::PlainsDamp,
-            "plains, desert" => AssetTag::PlainsDesert,
-            "plains, farmland" => AssetTag::PlainsFarmland,
-            "plains, lush" => AssetTag::PlainsLush,
-            "ruins, desert" => AssetTag::RuinDesert,
-            "ruins, lush" => AssetTag::RuinLush,
-            "snow area" => AssetTag::SnowArea,
-            "snow drifts" => AssetTag::SnowDrifts,
-            "snow field" => AssetTag::SnowField,
-            "sparse trees, lush" => AssetTag::SparseTreesLush,
-            "sparse trees, snowy" => AssetTag::SparseTreesSnowy,
-            "urban city, lush" => AssetTag::UrbanCityLush,
-            "urban farm, lush" => AssetTag::UrbanFarmLush,
-            "urban farmland, lush" => AssetTag::UrbanFarmlandLush,
-            "urban town, abandoned" => AssetTag::UrbanTownAbandoned,
-            "urban town, inhabited" => AssetTag::UrbanTownInhabited,
-            "urban town lumberyard" => AssetTag::UrbanTownLumberyardLush,
-            "urban monastery" => AssetTag::UrbanMonasteryLush,
-            "urban tower" => AssetTag::UrbanTowerLush,
-            "urban town, lush" => AssetTag::UrbanTownLush,
-            "ocean soft waves" => AssetTag::OceanSoftWaves,
-            "ocean still water" => AssetTag::OceanStill,
-            "ocean waves" => AssetTag::OceanWaves,
-            "wetlands, damp" => AssetTag::WetlandsDamp,
-            _ => AssetTag::None,
+    /// The coast tag of a given size facing a given direction.
+    pub fn coast(size: CoastSize, direction: HexDirection) -> AssetTag {
+        COAST_TAGS
+            .iter()
+            .find(|(_, s, d)| *s == size && *d == direction)
+            .map(|(tag, _, _)| *tag)
+            .expect("every size and direction has a coast tag")
+    }
+
+    pub fn biome(self) -> Option<Biome> {
+        let name: &'static str = self.into();
+        Biome::iter().find(|biome| name.ends_with(<&'static str>::from(*biome)))
+    }
+
+    /// The same terrain drawn for another biome, e.g. `HillsLush` to `HillsSnowy`.
+    /// `None` if the tag has no biome or there is no such tag.
+    pub fn with_biome(self, biome: Biome) -> Option<AssetTag> {
+        let name: &'static str = self.into();
+        let current: &'static str = self.biome()?.into();
+        let terrain = &name[..name.len() - current.len()];
+        format!("{}{}", terrain, <&'static str>::from(biome))
+            .parse()
+            .ok()
+    }
+
+    /// Size and facing of a coast tag, or `None` for any other tag.
+    pub fn coast_facing(self) -> Option<(CoastSize, HexDirection)> {
+        COAST_TAGS
+            .iter()
+            .find(|(tag, _, _)| *tag == self)
+            .map(|(_, size, direction)| (*size, *direction))
+    }
+
+    /// The tag of a hex mirrored across the east-west axis (if `mirrored`) and then turned
+    /// counterclockwise by `rotation` times 60°. Coast tags are swapped for the ones facing
+    /// the turned direction; other tags stay the same.
+    pub fn turned(self, rotation: u8, mirrored: bool) -> AssetTag {
+        let Some((size, direction)) = self.coast_facing() else {
+            return self;
+        };
+        let steps = direction.steps_ccw();
+        let steps = match (mirrored, size) {
+            (false, _) => steps,
+            // A medium beach is named for the first of its two edges, counterclockwise, which
+            // the mirror turns into the last.
+            (true, CoastSize::Medium) => (11 - steps) % 6,
+            (true, _) => (6 - steps) % 6,
+        };
+        AssetTag::coast(size, HexDirection::from_steps_ccw(steps + rotation % 6))
+    }
+}
+
+/// How a tile's art is turned before it is drawn, so facings missing from the art pack
+/// can be derived from the ones it has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TileFacing {
+    /// Counterclockwise rotation in 60° steps.
+    pub rotation: u8,
+    /// Mirror the art horizontally (applied before the rotation).
+    pub flip_x: bool,
+}
+
+impl TileFacing {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_z(self.rotation as f32 * std::f32::consts::FRAC_PI_3)
+    }
+}
+
+/// One entry of the tile catalog (`assets/tiles.catalog.json`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TileDefinition {
+    pub id: AssetTag,
+    /// Label shown in the toolbox.
+    pub name: String,
+    /// Toolbox group the tile is listed under.
+    pub group: String,
+    /// Image files in `assets/hextiles`.
+    pub files: Vec<String>,
+    /// Open water, as opposed to land.
+    #[serde(default)]
+    pub water: bool,
+    /// Land that auto-coast may turn into a beach when it borders water.
+    #[serde(default)]
+    pub coastable: bool,
+    /// Counterclockwise rotation of the art in 60° steps.
+    #[serde(default)]
+    pub rotation: u8,
+    /// Mirror the art horizontally (applied before the rotation).
+    #[serde(default)]
+    pub flip_x: bool,
+}
+
+impl TileDefinition {
+    pub fn facing(&self) -> TileFacing {
+        TileFacing {
+            rotation: self.rotation % 6,
+            flip_x: self.flip_x,
         }
     }
 }
 
-pub fn get_asset_for_tag(tag: AssetTag) -> Option<&'static str> {
-    match tag {
-        AssetTag::Blank => Some("Hex - Base (blank).webp"),
-        AssetTag::Outline => Some("Hex - Base (outline).webp"),
-        AssetTag::BaseLush => Some("Hex - Base (lush).webp"),
-        AssetTag::BaseOcean => Some("Hex - Base (ocean).webp"),
-        AssetTag::BaseRocky => Some("Hex - Base (rocky).webp"),
-        AssetTag::BaseSnowy => Some("Hex - Base (snowy).webp"),
-        AssetTag::ForestConiferLush => Some("Hex - Forest, conifer (lush).webp"),
-        AssetTag::ForestConiferSnowy => Some("Hex - Forest, conifer (snowy).webp"),
-        AssetTag::ForestDeciduousLush => Some("Hex - Forest, deciduous (lush).webp"),
-        AssetTag::ForestMixedLush => Some("Hex - Forest, mixed (lush).webp"),
-        AssetTag::HillsDesert => Some("Hex - Hills (desert) 1.webp"),
-        AssetTag::HillsLush => Some("Hex - Hills (lush) 1.webp"),
-        AssetTag::HillsSnowy => Some("Hex - Hills (snowy) 1.webp"),
-        AssetTag::MountainVolcanoLush => Some("Hex - Mountain, Volcano (lush) 1.webp"),
-        AssetTag::MountainVolcanoRocky => Some("Hex - Mountain, Volcano (rocky) 1.webp"),
-        AssetTag::MountainVolcanoSnowy => Some("Hex - Mountain, Volcano (snowy) 1.webp"),
-        AssetTag::MountainFoothillsLush => Some("Hex - Mountains, foothills (lush).webp"),
-        AssetTag::MountainFoothillsRocky => Some("Hex - Mountains, foothills (rocky).webp"),
-        AssetTag::MountainFoothillsSnowy => Some("Hex - Mountains, foothills (snowy).webp"),
-        AssetTag::MountainLowLush => Some("Hex - Mountains, low (lush).webp"),
-        AssetTag::MountainLowRocky => Some("Hex - Mountains, low (rocky).webp"),
-        AssetTag::MountainLowSnowy => Some("Hex - Mountains, low (snowy).webp"),
-        AssetTag::MountainMediumLush => Some("Hex - Mountains, medium (lush).webp"),
-        AssetTag::MountainMediumRocky => Some("Hex - Mountains, medium (rocky).webp"),
-        AssetTag::MountainMediumSnowy => Some("Hex - Mountains, medium (snowy).webp"),
-        AssetTag::MountainPeakLush => Some("Hex - Mountains, peak (lush).webp"),
-        AssetTag::MountainPeakRocky => Some("Hex - Mountains, peak (rocky).webp"),
-        AssetTag::MountainPeakSnowy => Some("Hex - Mountains, peak (snowy).webp"),
-        AssetTag::PlainsDamp => Some("Hex - Plains (damp) 1.webp"),
-        AssetTag::PlainsDesert => Some("Hex - Plains (desert) 4.webp"),
-        AssetTag::PlainsFarmland => Some("Hex - Plains (farmland) 1.webp"),
-        AssetTag::PlainsLush => Some("Hex - Plains (lush) 5.webp"),
-        AssetTag::RuinDesert => Some("Hex - Ruin (desert).webp"),
-        AssetTag::RuinLush => Some("Hex - Ruin (lush).webp"),
-        AssetTag::SnowArea => Some("Hex - Snow (area) 1.webp"),
-        AssetTag::SnowDrifts => Some("Hex - Snow (drifts) 1.webp"),
-        AssetTag::SnowField => Some("Hex - Snow (field) 1.webp"),
-        AssetTag::SparseTreesLush => Some("Hex - Sparse Trees (lush) 1.webp"),
-        AssetTag::SparseTreesSnowy => Some("Hex - Sparse Trees (snowy).webp"),
-        AssetTag::UrbanCityLush => Some("Hex - Urban - City (lush).webp"),
-        AssetTag::UrbanFarmLush => Some("Hex - Urban - Farm (lush).webp"),
-        AssetTag::UrbanFarmlandLush => Some("Hex - Urban - Farmland (lush) 1.webp"),
-        AssetTag::UrbanTownAbandoned => Some("Hex - Urban - Modern Town, abandoned (lush) 1.webp"),
-        AssetTag::UrbanTownInhabited => Some("Hex - Urban - Modern Town, inhabited (lush) 1.webp"),
-        AssetTag::UrbanTownLumberyardLush => {
-            Some("Hex - Urban - Modern Town, lumber yard (lush).webp")
+/// The list of paintable tiles, shared by Rust and the JavaScript toolbox.
+///
+/// Loaded as an asset and, once loaded, also inserted as a resource so systems can read it
+/// directly. Tags that are missing from the catalog have no image and cannot be selected.
+#[derive(Asset, Resource, TypePath, Serialize, Deserialize, Debug, Clone)]
+pub struct TileCatalog {
+    pub tiles: Vec<TileDefinition>,
+}
+
+/// A toolbox group as handed to JavaScript by `list_tiles`.
+#[derive(Serialize, Debug, Clone)]
+pub struct PaletteGroup {
+    pub name: String,
+    pub tiles: Vec<PaletteTile>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PaletteTile {
+    pub id: &'static str,
+    pub name: String,
+    /// Path of the tile image, relative to the page.
+    pub thumbnail: Option<String>,
+}
+
+impl TileCatalog {
+    pub fn get(&self, tag: AssetTag) -> Option<&TileDefinition> {
+        self.tiles.iter().find(|tile| tile.id == tag)
+    }
+
+    pub fn is_water(&self, tag: AssetTag) -> bool {
+        self.get(tag).is_some_and(|tile| tile.water)
+    }
+
+    pub fn is_coastable(&self, tag: AssetTag) -> bool {
+        self.get(tag).is_some_and(|tile| tile.coastable)
+    }
+
+    /// Groups the tiles for the toolbox, keeping the catalog order of groups and tiles.
+    /// The eraser, which is not a tile of the catalog, leads the first group.
+    pub fn palette(&self) -> Vec<PaletteGroup> {
+        let mut groups: Vec<PaletteGroup> = Vec::new();
+        for tile in &self.tiles {
+            let entry = PaletteTile {
+                id: tile.id.into(),
+                name: tile.name.clone(),
+                thumbnail: thumbnail(&tile.files),
+            };
+            match groups.iter_mut().find(|group| group.name == tile.group) {
+                Some(group) => group.tiles.push(entry),
+                None => groups.push(PaletteGroup {
+                    name: tile.group.clone(),
+                    tiles: vec![entry],
+                }),
+            }
         }
-        AssetTag::UrbanMonasteryLush => Some("Hex - Urban - Monastery (lush).webp"),
-        AssetTag::UrbanTowerLush => Some("Hex - Urban - Tower (lush).webp"),
-        AssetTag::UrbanTownLush => Some("Hex - Urban - Town (lush).webp"),
-        AssetTag::OceanSoftWaves => Some("Hex - Water - Ocean (soft waves) 1.webp"),
-        AssetTag::OceanStill => Some("Hex - Water - Ocean (still water) 1.webp"),
-        AssetTag::OceanWaves => Some("Hex - Water - Ocean (waves) 1.webp"),
-        AssetTag::SwampSoftWaves => Some("Hex - Water - Swamp (soft waves) 1.webp"),
-        AssetTag::SwampStill => Some("Hex - Water - Swamp (still water) 1.webp"),
-        AssetTag::SwanpWaves => Some("Hex - Water - Swamp (waves) 1.webp"),
-        AssetTag::WetlandsDamp => Some("Hex - Wetlands (damp) 1.webp"),
-        _ => None,
+
+        let eraser = PaletteTile {
+            id: AssetTag::None.into(),
+            name: "Delete (eraser)".to_string(),
+            thumbnail: self
+                .get(AssetTag::Outline)
+                .and_then(|outline| thumbnail(&outline.files)),
+        };
+        match groups.first_mut() {
+            Some(group) => group.tiles.insert(0, eraser),
+            None => groups.push(PaletteGroup {
+                name: String::new(),
+                tiles: vec![eraser],
+            }),
+        }
+        groups
     }
 }
 
-pub fn load_tag(
-    asset_tag: AssetTag,
-    asset_server: &AssetServer,
-    missing_handle: Handle<Image>,
-) -> Handle<Image> {
-    match get_asset_for_tag(asset_tag) {
-        Some(path) => asset_server.load(format!("hextiles/{}", path)),
-        None => missing_handle,
+/// Path of a tile's thumbnail, relative to the page.
+fn thumbnail(files: &[String]) -> Option<String> {
+    files
+        .first()
+        .map(|file| format!("assets/hextiles/{}", file))
+}
+
+/// Handle to the tile catalog asset, kept alive for the lifetime of the app.
+#[derive(Resource)]
+pub struct TileCatalogHandle(pub Handle<TileCatalog>);
+
+/// Loads `*.catalog.json` files into a `TileCatalog`.
+#[derive(Default)]
+pub struct TileCatalogLoader;
+
+impl AssetLoader for TileCatalogLoader {
+    type Asset = TileCatalog;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
     }
+
+    fn extensions(&self) -> &[&str] {
+        &["catalog.json"]
+    }
+}
+
+pub fn load_tile_image(file: &str, asset_server: &AssetServer) -> Handle<Image> {
+    asset_server.load(format!("hextiles/{}", file))
 }
 
 // #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -249,3 +462,99 @@ pub fn load_tag(
 //     }
 //     return asset_handles;
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Edges the beach of each coast image lies along, in the pointy-top art frame. The file
+    /// names give the flat-top screen direction instead, a 90° counterclockwise turn away.
+    const COAST_ART: [(&str, &[HexDirection]); 10] = {
+        use HexDirection::*;
+        [
+            ("Hex - Coast - Beach (small) N.webp", &[East]),
+            ("Hex - Coast - Beach (small) NW.webp", &[NorthEast]),
+            ("Hex - Coast - Beach (small) S.webp", &[West]),
+            ("Hex - Coast - Beach (medium) NW.webp", &[East, NorthEast]),
+            (
+                "Hex - Coast - Beach (medium) W.webp",
+                &[NorthEast, NorthWest],
+            ),
+            ("Hex - Coast - Beach (medium) SW.webp", &[NorthWest, West]),
+            (
+                "Hex - Coast - Beach (big) N.webp",
+                &[SouthEast, East, NorthEast],
+            ),
+            (
+                "Hex - Coast - Beach (big) NW.webp",
+                &[East, NorthEast, NorthWest],
+            ),
+            (
+                "Hex - Coast - Beach (big) S.webp",
+                &[NorthWest, West, SouthWest],
+            ),
+            (
+                "Hex - Coast - Beach (big) SW.webp",
+                &[NorthEast, NorthWest, West],
+            ),
+        ]
+    };
+
+    fn catalog() -> TileCatalog {
+        serde_json::from_str(include_str!("../assets/tiles.catalog.json")).unwrap()
+    }
+
+    /// Where an edge of the art ends up once the facing is applied.
+    fn face(facing: TileFacing, direction: HexDirection) -> HexDirection {
+        let steps = direction.steps_ccw();
+        let steps = if facing.flip_x {
+            (9 - steps) % 6
+        } else {
+            steps
+        };
+        HexDirection::from_steps_ccw(steps + facing.rotation)
+    }
+
+    #[test]
+    fn palette_starts_with_the_eraser() {
+        let catalog = catalog();
+        let palette = catalog.palette();
+        let eraser = &palette[0].tiles[0];
+        assert_eq!(eraser.id, <&'static str>::from(AssetTag::None));
+        assert!(eraser.thumbnail.is_some());
+        let tiles: usize = palette.iter().map(|group| group.tiles.len()).sum();
+        assert_eq!(tiles, catalog.tiles.len() + 1);
+    }
+
+    #[test]
+    fn coast_tiles_face_their_water() {
+        let catalog = catalog();
+        for (tag, size, direction) in COAST_TAGS {
+            let tile = catalog.get(tag).expect("every coast tag is in the catalog");
+            let steps = direction.steps_ccw();
+            // A medium beach starts at its direction; small and big ones are centred on it.
+            let expected: Vec<u8> = match size {
+                CoastSize::Small => vec![steps],
+                CoastSize::Medium => vec![steps, steps + 1],
+                CoastSize::Big => vec![steps + 5, steps, steps + 1],
+            };
+            let mut expected: Vec<HexDirection> = expected
+                .into_iter()
+                .map(HexDirection::from_steps_ccw)
+                .collect();
+
+            let (_, art) = COAST_ART
+                .iter()
+                .find(|(file, _)| tile.files == [*file])
+                .unwrap_or_else(|| panic!("{:?} uses an unknown coast image", tag));
+            let mut faced: Vec<HexDirection> = art
+                .iter()
+                .map(|direction| face(tile.facing(), *direction))
+                .collect();
+
+            expected.sort_by_key(|direction| direction.steps_ccw());
+            faced.sort_by_key(|direction| direction.steps_ccw());
+            assert_eq!(faced, expected, "{:?} faces the wrong way", tag);
+        }
+    }
+}
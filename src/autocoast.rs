@@ -0,0 +1,175 @@
+use hexgridspiral as hgs;
+use std::collections::HashSet;
+
+use crate::asset_loading::{AssetTag, CoastSize, TileCatalog};
+use crate::hex::{self, HexDirection};
+use crate::map::{HexData, HexMap};
+
+/// Land a coast hex turns back into once it no longer borders any water, if it was not
+/// placed by auto-coast and so has no land of its own to go back to.
+pub const INLAND_TAG: AssetTag = AssetTag::BaseLush;
+
+/// Works out the coast tiles around the given hexes.
+///
+/// Every existing hex among them and their neighbours that is either coastable land or
+/// already a coast tile is checked against its water neighbours. Land that becomes coast
+/// remembers its tag in `HexData::land`, and gets it back once the water is gone. Returns
+/// the hexes whose tag should change, with their new data; other land is never touched.
+pub fn coast_fixes(
+    map: &HexMap,
+    catalog: &TileCatalog,
+    around: impl IntoIterator<Item = hgs::TileIndex>,
+) -> Vec<(hgs::TileIndex, HexData)> {
+    let mut candidates = HashSet::new();
+    for index in around {
+        candidates.insert(index);
+        candidates.extend(hex::neighbours(index).map(|(_, neighbour)| neighbour));
+    }
+
+    let mut fixes = Vec::new();
+    for index in candidates {
+        let Some(data) = map.get(index) else {
+            continue;
+        };
+        let is_coast = data.tag.coast_facing().is_some();
+        if !is_coast && !catalog.is_coastable(data.tag) {
+            continue;
+        }
+
+        let water = hex::neighbours(index)
+            .map(|(_, neighbour)| map.get(neighbour).is_some_and(|n| catalog.is_water(n.tag)));
+        let land = if is_coast { data.land } else { Some(data.tag) };
+        let fixed = match coast_facing(water) {
+            Some((size, direction)) => HexData {
+                land,
+                ..HexData::new(AssetTag::coast(size, direction))
+            },
+            None if is_coast => HexData::new(land.unwrap_or(INLAND_TAG)),
+            None => continue,
+        };
+        if fixed.tag != data.tag {
+            fixes.push((index, fixed));
+        }
+    }
+    fixes
+}
+
+/// Picks the beach size from the number of water neighbours and faces it towards the middle
+/// of the longest run of adjacent water neighbours. `water` is indexed counterclockwise from
+/// east, like `HexDirection::ALL`.
+///
+/// Returns `None` without water, and also for a one-hex island: no coast tile has water on
+/// every side, so the island stays (or turns back into) plain land.
+fn coast_facing(water: [bool; 6]) -> Option<(CoastSize, HexDirection)> {
+    let size = match water.iter().filter(|&&is_water| is_water).count() {
+        0 | 6 => return None,
+        1 => CoastSize::Small,
+        2 => CoastSize::Medium,
+        _ => CoastSize::Big,
+    };
+
+    // (length, first direction) of the longest run of water.
+    let mut longest = (0, 0);
+    for start in 0..6 {
+        // A run starts right after a land neighbour.
+        if !water[start] || water[(start + 5) % 6] {
+            continue;
+        }
+        let length = (0..6).take_while(|k| water[(start + k) % 6]).count();
+        if length > longest.0 {
+            longest = (length, start);
+        }
+    }
+    let (length, start) = longest;
+    let middle = (start + (length - 1) / 2) as u8;
+    Some((size, HexDirection::from_steps_ccw(middle)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_loading::TileDefinition;
+
+    fn catalog() -> TileCatalog {
+        let tile = |id, water, coastable| TileDefinition {
+            id,
+            name: String::new(),
+            group: String::new(),
+            files: Vec::new(),
+            water,
+            coastable,
+            rotation: 0,
+            flip_x: false,
+        };
+        TileCatalog {
+            tiles: vec![
+                tile(AssetTag::OceanStill, true, false),
+                tile(AssetTag::PlainsLush, false, true),
+            ],
+        }
+    }
+
+    fn fix_at(map: &HexMap, index: hgs::TileIndex) -> Option<HexData> {
+        coast_fixes(map, &catalog(), [index])
+            .into_iter()
+            .find(|(fixed, _)| *fixed == index)
+            .map(|(_, data)| data)
+    }
+
+    #[test]
+    fn beach_faces_the_middle_of_the_water() {
+        let water = |directions: &[HexDirection]| {
+            HexDirection::ALL.map(|direction| directions.contains(&direction))
+        };
+        use HexDirection::*;
+        assert_eq!(coast_facing(water(&[])), None);
+        assert_eq!(coast_facing(water(&[West])), Some((CoastSize::Small, West)));
+        assert_eq!(
+            coast_facing(water(&[SouthEast, East])),
+            Some((CoastSize::Medium, SouthEast))
+        );
+        assert_eq!(
+            coast_facing(water(&[East, NorthEast, NorthWest])),
+            Some((CoastSize::Big, NorthEast))
+        );
+    }
+
+    #[test]
+    fn island_stays_land() {
+        let origin = hex::index_of((0, 0, 0));
+        let mut map = HexMap::default();
+        map.set(origin, HexData::new(AssetTag::PlainsLush));
+        for (_, neighbour) in hex::neighbours(origin) {
+            map.set(neighbour, HexData::new(AssetTag::OceanStill));
+        }
+        assert_eq!(fix_at(&map, origin), None);
+    }
+
+    #[test]
+    fn coast_turns_back_into_its_land() {
+        let origin = hex::index_of((0, 0, 0));
+        let east = hex::index_of(HexDirection::East.offset());
+        let mut map = HexMap::default();
+        map.set(origin, HexData::new(AssetTag::PlainsLush));
+        map.set(east, HexData::new(AssetTag::OceanStill));
+
+        let coast = fix_at(&map, origin).expect("land next to water becomes coast");
+        assert_eq!(coast.tag, AssetTag::CoastSmallE);
+        assert_eq!(coast.land, Some(AssetTag::PlainsLush));
+
+        map.set(origin, coast);
+        map.remove(east);
+        assert_eq!(
+            fix_at(&map, origin),
+            Some(HexData::new(AssetTag::PlainsLush))
+        );
+    }
+
+    #[test]
+    fn hand_placed_coast_turns_inland() {
+        let origin = hex::index_of((0, 0, 0));
+        let mut map = HexMap::default();
+        map.set(origin, HexData::new(AssetTag::CoastBigW));
+        assert_eq!(fix_at(&map, origin), Some(HexData::new(INLAND_TAG)));
+    }
+}
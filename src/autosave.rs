@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::js_events;
+use crate::map_document::MapDocument;
+
+/// Number of rolling snapshots kept in `localStorage`.
+pub const AUTOSAVE_SLOTS: usize = 5;
+/// Seconds without edits before the map is written to storage.
+pub const AUTOSAVE_DELAY_SECS: f32 = 2.0;
+/// Minimum age (in milliseconds) of the newest snapshot before a new slot is started.
+/// Saves within this window overwrite the newest snapshot instead of rotating.
+const SNAPSHOT_INTERVAL_MS: f64 = 60_000.0;
+const AUTOSAVE_KEY_PREFIX: &str = "double_hexxed.autosave.";
+
+/// A map snapshot as stored in `localStorage`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AutosaveEntry {
+    /// Milliseconds since the Unix epoch, as reported by `Date.now()`.
+    pub saved_at: f64,
+    /// `map_hash` of the map's JSON, so an unchanged map is not saved again.
+    #[serde(default)]
+    pub hash: u64,
+    pub map: MapDocument,
+}
+
+impl AutosaveEntry {
+    pub fn summary(&self, slot: usize) -> AutosaveSummary {
+        AutosaveSummary {
+            slot,
+            saved_at: self.saved_at,
+            tiles: self.map.tiles.len(),
+        }
+    }
+}
+
+/// The fields of an `AutosaveEntry` that autosave keeps track of, read without building
+/// the map.
+#[derive(Deserialize, Debug, Clone, Copy)]
+struct AutosaveHeader {
+    saved_at: f64,
+    #[serde(default)]
+    hash: u64,
+}
+
+/// Lightweight description of a snapshot, handed to JavaScript by `list_autosaves` and
+/// the `autosaveavailable` event.
+#[derive(Serialize, Debug, Clone)]
+pub struct AutosaveSummary {
+    pub slot: usize,
+    pub saved_at: f64,
+    pub tiles: usize,
+}
+
+pub fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+fn slot_key(slot: usize) -> String {
+    format!("{}{}", AUTOSAVE_KEY_PREFIX, slot)
+}
+
+/// Reads a snapshot, ignoring slots that are empty or no longer parse.
+pub fn read_autosave(slot: usize) -> Option<AutosaveEntry> {
+    let json = local_storage()?.get_item(&slot_key(slot)).ok()??;
+    serde_json::from_str(&json).ok()
+}
+
+fn read_header(storage: &web_sys::Storage, slot: usize) -> Option<AutosaveHeader> {
+    let json = storage.get_item(&slot_key(slot)).ok()??;
+    serde_json::from_str(&json).ok()
+}
+
+/// All stored snapshots, newest first.
+pub fn list_autosaves() -> Vec<(usize, AutosaveEntry)> {
+    let mut entries: Vec<(usize, AutosaveEntry)> = (0..AUTOSAVE_SLOTS)
+        .filter_map(|slot| read_autosave(slot).map(|entry| (slot, entry)))
+        .collect();
+    entries.sort_by(|a, b| b.1.saved_at.total_cmp(&a.1.saved_at));
+    entries
+}
+
+/// Stable FNV-1a hash of a map's JSON, computed without building the JSON string.
+fn map_hash(map: &MapDocument) -> u64 {
+    struct Fnv(u64);
+    impl std::io::Write for Fnv {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            for &byte in bytes {
+                self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x100_0000_01B3);
+            }
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut hasher = Fnv(0xCBF2_9CE4_8422_2325);
+    serde_json::to_writer(&mut hasher, map).expect("map document is always serializable");
+    hasher.0
+}
+
+/// Writes the map to rolling snapshots in `localStorage`.
+///
+/// The slots are read once, on the first save; after that the autosaver remembers what it
+/// wrote. When storage is full, the oldest snapshots are dropped to make room. If even a
+/// single snapshot does not fit, autosave stops and the page gets an `autosavestopped`
+/// event.
+#[derive(Debug, Default)]
+pub struct Autosaver {
+    /// Save time and map hash of each slot, `None` until the slots have been read.
+    slots: Option<[Option<AutosaveHeader>; AUTOSAVE_SLOTS]>,
+    stopped: bool,
+}
+
+impl Autosaver {
+    /// Saves the map, rotating into the oldest slot when the newest snapshot is older than
+    /// `SNAPSHOT_INTERVAL_MS`. Does nothing if the map is the one saved last.
+    pub fn save(&mut self, map: &MapDocument) {
+        if self.stopped {
+            return;
+        }
+        let Some(storage) = local_storage() else {
+            return;
+        };
+        let slots = self
+            .slots
+            .get_or_insert_with(|| std::array::from_fn(|slot| read_header(&storage, slot)));
+
+        let hash = map_hash(map);
+        let now = js_sys::Date::now();
+        let newest = (0..AUTOSAVE_SLOTS)
+            .filter_map(|slot| slots[slot].map(|header| (slot, header)))
+            .max_by(|a, b| a.1.saved_at.total_cmp(&b.1.saved_at));
+        let slot = match newest {
+            // Nothing changed since the last snapshot (e.g. right after a restore).
+            Some((_, newest)) if newest.hash == hash => return,
+            Some((slot, newest)) if now - newest.saved_at < SNAPSHOT_INTERVAL_MS => slot,
+            _ => oldest_slot(slots, None).unwrap_or(0),
+        };
+
+        let entry = AutosaveEntry {
+            saved_at: now,
+            hash,
+            map: map.clone(),
+        };
+        let json = serde_json::to_string(&entry).expect("autosave entry is always serializable");
+        while let Err(err) = storage.set_item(&slot_key(slot), &json) {
+            // Storage is full: make room by dropping the oldest other snapshot.
+            let Some(oldest) =
+                oldest_slot(slots, Some(slot)).filter(|&oldest| slots[oldest].is_some())
+            else {
+                log::warn!("Autosave failed: {:?}", err);
+                self.stopped = true;
+                js_events::dispatch(
+                    "autosavestopped",
+                    &JsValue::from_str("The map is too large for the browser's storage"),
+                );
+                return;
+            };
+            if let Err(err) = storage.remove_item(&slot_key(oldest)) {
+                log::warn!("Could not drop autosave slot {}: {:?}", oldest, err);
+            }
+            slots[oldest] = None;
+        }
+        slots[slot] = Some(AutosaveHeader {
+            saved_at: now,
+            hash,
+        });
+    }
+}
+
+/// An empty slot, or else the slot with the oldest snapshot, leaving out `except`.
+fn oldest_slot(
+    slots: &[Option<AutosaveHeader>; AUTOSAVE_SLOTS],
+    except: Option<usize>,
+) -> Option<usize> {
+    (0..AUTOSAVE_SLOTS)
+        .filter(|slot| Some(*slot) != except)
+        .min_by(|&a, &b| {
+            let saved_at = |slot: usize| slots[slot].map_or(f64::NEG_INFINITY, |h| h.saved_at);
+            saved_at(a).total_cmp(&saved_at(b))
+        })
+}
+
+/// Tells the page about the newest snapshot with an `autosaveavailable` event, so it can
+/// offer to restore the last session.
+pub fn offer_restore() {
+    let Some((slot, entry)) = list_autosaves().into_iter().next() else {
+        return;
+    };
+    let summary = serde_wasm_bindgen::to_value(&entry.summary(slot)).unwrap_or(JsValue::NULL);
+    js_events::dispatch("autosaveavailable", &summary);
+}
@@ -0,0 +1,93 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::render_resource::PrimitiveTopology;
+use hexgridspiral as hgs;
+
+use crate::hex::{self, Cube};
+use crate::layout::{Orientation, tile_depth};
+use crate::tile_config::image_size;
+
+/// Hexes per chunk edge. Chunks are parallelograms of `CHUNK_SIZE × CHUNK_SIZE` hexes.
+pub const CHUNK_SIZE: i64 = 16;
+
+/// Position of a chunk in the grid of chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkCoord(pub i64, pub i64);
+
+impl ChunkCoord {
+    pub fn of(index: hgs::TileIndex) -> Self {
+        let (q, r, _) = hex::cube(index);
+        ChunkCoord(q.div_euclid(CHUNK_SIZE), r.div_euclid(CHUNK_SIZE))
+    }
+
+    /// World-space rectangle covering every tile drawn for the chunk.
+    pub fn bounds(self, orientation: Orientation) -> Rect {
+        let (q0, r0) = (self.0 * CHUNK_SIZE, self.1 * CHUNK_SIZE);
+        let (q1, r1) = (q0 + CHUNK_SIZE - 1, r0 + CHUNK_SIZE - 1);
+        let corners: [Cube; 4] = [
+            (q0, r0, -q0 - r0),
+            (q1, r0, -q1 - r0),
+            (q0, r1, -q0 - r1),
+            (q1, r1, -q1 - r1),
+        ];
+        let mut bounds = Rect::from_center_size(orientation.world_position(corners[0]), Vec2::ZERO);
+        for corner in &corners[1..] {
+            bounds = bounds.union_point(orientation.world_position(*corner));
+        }
+        let (width, height) = image_size();
+        // Tiles may be drawn rotated, so pad by the larger half-extent either way.
+        bounds.inflate(width.max(height) as f32 / 2.)
+    }
+}
+
+/// A tile drawn as part of a chunk mesh.
+pub struct TileQuad {
+    pub position: Vec2,
+    pub rotation: Quat,
+    /// Mirror the art horizontally (applied before the rotation).
+    pub flip_x: bool,
+    /// Tint multiplied into the art.
+    pub color: Color,
+}
+
+/// Mesh drawing each quad as a tile-sized rectangle of the tile art, at the depth
+/// `tile_depth` gives its position. All quads of one mesh share a texture.
+pub fn tile_mesh(quads: &[TileQuad]) -> Mesh {
+    let (width, height) = image_size();
+    let half_size = Vec2::new(width as f32, height as f32) / 2.;
+    let corners = [
+        Vec2::new(-1., -1.),
+        Vec2::new(1., -1.),
+        Vec2::new(1., 1.),
+        Vec2::new(-1., 1.),
+    ];
+
+    let mut positions = Vec::with_capacity(quads.len() * 4);
+    let mut uvs = Vec::with_capacity(quads.len() * 4);
+    let mut colors = Vec::with_capacity(quads.len() * 4);
+    let mut indices = Vec::with_capacity(quads.len() * 6);
+    for quad in quads {
+        let first = positions.len() as u32;
+        let centre = quad.position.extend(tile_depth(quad.position.y));
+        let color = quad.color.to_linear();
+        for corner in corners {
+            let offset = quad.rotation * (corner * half_size).extend(0.);
+            positions.push((centre + offset).to_array());
+            let u = if quad.flip_x { -corner.x } else { corner.x };
+            // Image rows run top to bottom.
+            uvs.push([(1. + u) / 2., (1. - corner.y) / 2.]);
+            colors.push([color.red, color.green, color.blue, color.alpha]);
+        }
+        indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
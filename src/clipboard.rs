@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::autosave::local_storage;
+use crate::hex::{self, Cube};
+use crate::map::{HexData, HexMap};
+
+const STAMPS_KEY: &str = "double_hexxed.stamps";
+
+/// Hexes lifted off the map, kept as offsets from their centre so they can be put down
+/// anywhere, turned and mirrored. Both the clipboard and saved stamps hold one.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Pattern {
+    /// Cube offset from the pattern's centre and the full data of each hex.
+    pub hexes: Vec<(Cube, HexData)>,
+}
+
+impl Pattern {
+    /// Copies the given hexes that exist in the map. The hex nearest the middle of the
+    /// copied hexes becomes the centre, which lands under the cursor when pasting.
+    pub fn copy(map: &HexMap, indices: impl IntoIterator<Item = hgs::TileIndex>) -> Self {
+        let mut hexes: Vec<(Cube, HexData)> = indices
+            .into_iter()
+            .filter_map(|index| map.get(index).map(|data| (hex::cube(index), *data)))
+            .collect();
+        if hexes.is_empty() {
+            return Self::default();
+        }
+
+        let count = hexes.len() as f64;
+        let (q, r, s) = hexes.iter().fold((0., 0., 0.), |sum, ((q, r, s), _)| {
+            (sum.0 + *q as f64, sum.1 + *r as f64, sum.2 + *s as f64)
+        });
+        let centre = hex::round((q / count, r / count, s / count));
+        for (cube, _) in &mut hexes {
+            *cube = hex::subtract(*cube, centre);
+        }
+        hexes.sort_by_key(|(cube, _)| *cube);
+        Self { hexes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hexes.is_empty()
+    }
+
+    /// The pattern mirrored (if `mirrored`) and then turned counterclockwise by `rotation`
+    /// times 60°. Coast tiles are swapped for the ones facing the turned direction.
+    pub fn transformed(&self, rotation: u8, mirrored: bool) -> Self {
+        let hexes = self
+            .hexes
+            .iter()
+            .map(|&(cube, data)| {
+                let cube = if mirrored { hex::mirror(cube) } else { cube };
+                let tag = data.tag.turned(rotation, mirrored);
+                (hex::rotate(cube, rotation), HexData { tag, ..data })
+            })
+            .collect();
+        Self { hexes }
+    }
+
+    /// Where each hex lands when the pattern's centre is put on `at`.
+    pub fn placed(
+        &self,
+        at: hgs::TileIndex,
+    ) -> impl Iterator<Item = (hgs::TileIndex, HexData)> + '_ {
+        let at = hex::cube(at);
+        self.hexes
+            .iter()
+            .map(move |&(cube, data)| (hex::index_of(hex::add(at, cube)), data))
+    }
+}
+
+/// What the paste tool puts down: the last copied pattern and how it is turned.
+#[derive(Resource, Debug, Default)]
+pub struct Clipboard {
+    pub pattern: Pattern,
+    /// Counterclockwise turn in 60° steps, 0 to 5.
+    pub rotation: u8,
+    /// Mirrored across the east-west axis, before turning.
+    pub mirrored: bool,
+}
+
+impl Clipboard {
+    /// Replaces the pattern, starting over unturned and unmirrored.
+    pub fn set(&mut self, pattern: Pattern) {
+        *self = Self {
+            pattern,
+            ..default()
+        };
+    }
+
+    /// The pattern as it would be pasted.
+    pub fn oriented(&self) -> Pattern {
+        self.pattern.transformed(self.rotation, self.mirrored)
+    }
+
+    /// Turns the pasted pattern by `steps` times 60°, counterclockwise.
+    pub fn rotate(&mut self, steps: u8) {
+        self.rotation = (self.rotation + steps) % 6;
+    }
+
+    pub fn toggle_mirror(&mut self) {
+        self.mirrored = !self.mirrored;
+    }
+}
+
+/// Named stamps saved in `localStorage`, sorted by name. Stamps that no longer parse are
+/// dropped.
+pub fn read_stamps() -> BTreeMap<String, Pattern> {
+    local_storage()
+        .and_then(|storage| storage.get_item(STAMPS_KEY).ok().flatten())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn write_stamps(stamps: &BTreeMap<String, Pattern>) {
+    let Some(storage) = local_storage() else {
+        return;
+    };
+    let json = serde_json::to_string(stamps).expect("stamps are always serializable");
+    if let Err(err) = storage.set_item(STAMPS_KEY, &json) {
+        log::warn!("Saving stamps failed: {:?}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_loading::AssetTag;
+    use crate::hex::HexDirection;
+
+    fn pattern(hexes: &[(HexDirection, AssetTag)]) -> Pattern {
+        Pattern {
+            hexes: hexes
+                .iter()
+                .map(|&(direction, tag)| (direction.offset(), HexData::new(tag)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn turned_patterns_turn_their_coasts() {
+        let original = pattern(&[
+            (HexDirection::East, AssetTag::CoastSmallE),
+            (HexDirection::West, AssetTag::PlainsLush),
+        ]);
+        assert_eq!(
+            original.transformed(1, false),
+            pattern(&[
+                (HexDirection::NorthEast, AssetTag::CoastSmallNE),
+                (HexDirection::SouthWest, AssetTag::PlainsLush),
+            ])
+        );
+        assert_eq!(original.transformed(6, false), original);
+    }
+
+    #[test]
+    fn mirrored_patterns_mirror_their_coasts() {
+        let original = pattern(&[(HexDirection::NorthEast, AssetTag::CoastSmallNE)]);
+        assert_eq!(
+            original.transformed(0, true),
+            pattern(&[(HexDirection::SouthEast, AssetTag::CoastSmallSE)])
+        );
+        assert_eq!(
+            original.transformed(1, true),
+            pattern(&[(HexDirection::East, AssetTag::CoastSmallE)])
+        );
+
+        // Water on the east and north-east edges ends up on the east and south-east ones.
+        let medium = pattern(&[(HexDirection::East, AssetTag::CoastMediumE)]);
+        assert_eq!(
+            medium.transformed(0, true),
+            pattern(&[(HexDirection::East, AssetTag::CoastMediumSE)])
+        );
+    }
+}
@@ -1,15 +1,13 @@
 use bevy::prelude::*;
-use hexgridspiral as hgs;
-
-use crate::asset_loading::AssetTag;
 
 /// Marker component for the main 2D camera.
 #[derive(Component)]
 pub struct MainCamera;
 
-/// Marker component attached to each hex tile with its spiral index.
+/// Marker component for the sprite that pops the hovered tile out of the map.
+#[derive(Component)]
+pub struct HoverMarker;
+
+/// Marker component for the translucent sprites showing `PreviewHexes`.
 #[derive(Component)]
-pub struct TileMarker {
-    pub index: hgs::TileIndex,
-    pub tag: AssetTag,
-}
+pub struct PreviewMarker;
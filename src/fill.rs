@@ -0,0 +1,101 @@
+use hexgridspiral as hgs;
+use std::collections::{HashSet, VecDeque};
+
+use crate::asset_loading::AssetTag;
+use crate::hex::{self, HexDirection};
+use crate::map::HexMap;
+
+/// Most hexes a single fill of empty space may add. Empty space is unbounded, so without a
+/// fill limit the region would run out into the plane; filling tiles is bounded by the map.
+pub const MAX_FILL_HEXES: usize = 100_000;
+
+/// The contiguous region of hexes sharing the tag of `start`, found by flood fill.
+///
+/// Empty hexes form regions too, so filling outside the map adds hexes. With `max_distance`
+/// set, the region stops that many steps from `start`. Returns an error instead of a partial
+/// region if a region of empty hexes would be larger than `MAX_FILL_HEXES`.
+pub fn flood_region(
+    map: &HexMap,
+    start: hgs::TileIndex,
+    max_distance: Option<u32>,
+) -> Result<Vec<hgs::TileIndex>, String> {
+    let target: Option<AssetTag> = map.get(start).map(|data| data.tag);
+    let origin = hex::cube(start);
+
+    let mut region = Vec::new();
+    let mut seen = HashSet::from([origin]);
+    let mut queue = VecDeque::from([origin]);
+    while let Some(cube) = queue.pop_front() {
+        region.push(hex::index_of(cube));
+        if target.is_none() && region.len() > MAX_FILL_HEXES {
+            return Err(format!(
+                "Fill region is larger than {} hexes; set a fill limit",
+                MAX_FILL_HEXES
+            ));
+        }
+
+        for direction in HexDirection::ALL {
+            let next = hex::add(cube, direction.offset());
+            let within_bound = max_distance
+                .is_none_or(|max_distance| hex::distance(origin, next) <= max_distance as i64);
+            if !within_bound || !seen.insert(next) {
+                continue;
+            }
+            let tag = map.get(hex::index_of(next)).map(|data| data.tag);
+            if tag == target {
+                queue.push_back(next);
+            }
+        }
+    }
+    Ok(region)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::HexData;
+
+    #[test]
+    fn fill_limit_bounds_the_region() {
+        let map = HexMap::default();
+        let start = hex::index_of((0, 0, 0));
+        let region = flood_region(&map, start, Some(2)).unwrap();
+        assert_eq!(region.len(), 19);
+        assert!(
+            region
+                .iter()
+                .all(|index| hex::distance((0, 0, 0), hex::cube(*index)) <= 2)
+        );
+    }
+
+    #[test]
+    fn unbounded_fill_of_empty_space_is_refused() {
+        let map = HexMap::default();
+        assert!(flood_region(&map, hex::index_of((0, 0, 0)), None).is_err());
+    }
+
+    #[test]
+    fn region_stops_at_other_tiles() {
+        let mut map = HexMap::default();
+        for cube in hex::range((0, 0, 0), 1) {
+            map.set(hex::index_of(cube), HexData::new(AssetTag::PlainsLush));
+        }
+        for cube in hex::ring((0, 0, 0), 2) {
+            map.set(hex::index_of(cube), HexData::new(AssetTag::HillsLush));
+        }
+        let region = flood_region(&map, hex::index_of((0, 0, 0)), None).unwrap();
+        assert_eq!(region.len(), 7);
+    }
+
+    #[test]
+    fn regions_of_tiles_are_bounded_by_the_map() {
+        let mut map = HexMap::default();
+        let sea = hex::range((0, 0, 0), 183);
+        assert!(sea.len() > MAX_FILL_HEXES);
+        for cube in &sea {
+            map.set(hex::index_of(*cube), HexData::new(AssetTag::OceanStill));
+        }
+        let region = flood_region(&map, hex::index_of((0, 0, 0)), None).unwrap();
+        assert_eq!(region.len(), sea.len());
+    }
+}
@@ -0,0 +1,307 @@
+use hexgridspiral as hgs;
+use once_cell::sync::Lazy;
+
+use crate::tile_config::step_size;
+
+/// The six edge directions of a hex, listed counterclockwise from east.
+///
+/// Directions are named for the unrotated, pointy-top tile art (the frame `hexgridspiral`
+/// pixel coordinates live in), not for the rotated map on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HexDirection {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast,
+}
+
+impl HexDirection {
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::East,
+        HexDirection::NorthEast,
+        HexDirection::NorthWest,
+        HexDirection::West,
+        HexDirection::SouthWest,
+        HexDirection::SouthEast,
+    ];
+
+    /// Number of 60° counterclockwise steps from east.
+    pub fn steps_ccw(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_steps_ccw(steps: u8) -> Self {
+        Self::ALL[(steps % 6) as usize]
+    }
+}
+
+/// Cube coordinates `(q, r, s)` of a hex, with `q + r + s == 0`.
+///
+/// The range, ring and line helpers below work on these tuples instead of calling
+/// `hexgridspiral`'s: rotation, mirroring, symmetry and the clipboard all share them, and
+/// `ring` walks the art directions of `DIRECTION_OFFSETS`, so going through the library's
+/// tile types would only add a conversion per hex.
+pub type Cube = (i64, i64, i64);
+
+/// Cube coordinates of a spiral index.
+pub fn cube(index: hgs::TileIndex) -> Cube {
+    hgs::HGSTile::new(index).cc().into_qrs_tuple()
+}
+
+/// Spiral index of a cube coordinate.
+pub fn index_of(cube: Cube) -> hgs::TileIndex {
+    let tile: hgs::HGSTile = hgs::CCTile::from_qrs(cube.0, cube.1, cube.2).into();
+    tile.spiral_index()
+}
+
+pub fn add(a: Cube, b: Cube) -> Cube {
+    (a.0 + b.0, a.1 + b.1, a.2 + b.2)
+}
+
+/// The six unit cube offsets, indexed by `HexDirection::steps_ccw`.
+///
+/// Which offset points which way depends on `hexgridspiral`'s pixel layout, so the table is
+/// built from the pixel position of each unit offset rather than hardcoded.
+static DIRECTION_OFFSETS: Lazy<[Cube; 6]> = Lazy::new(|| {
+    let units: [Cube; 6] = [
+        (1, -1, 0),
+        (1, 0, -1),
+        (0, 1, -1),
+        (-1, 1, 0),
+        (-1, 0, 1),
+        (0, -1, 1),
+    ];
+    let mut offsets = [(0, 0, 0); 6];
+    for unit in units {
+        let (x, y) =
+            hgs::CCTile::from_qrs(unit.0, unit.1, unit.2).to_irregular_pixel((0., 0.), step_size());
+        let steps = (y.atan2(x).to_degrees() / 60.).round().rem_euclid(6.) as u8;
+        offsets[steps as usize] = unit;
+    }
+    offsets
+});
+
+impl HexDirection {
+    /// Cube offset of the neighbour in this direction.
+    pub fn offset(self) -> Cube {
+        DIRECTION_OFFSETS[self.steps_ccw() as usize]
+    }
+}
+
+/// All six neighbours of a hex, counterclockwise from east.
+pub fn neighbours(index: hgs::TileIndex) -> [(HexDirection, hgs::TileIndex); 6] {
+    let centre = cube(index);
+    HexDirection::ALL.map(|direction| (direction, index_of(add(centre, direction.offset()))))
+}
+
+pub fn scale(cube: Cube, factor: i64) -> Cube {
+    (cube.0 * factor, cube.1 * factor, cube.2 * factor)
+}
+
+pub fn subtract(a: Cube, b: Cube) -> Cube {
+    (a.0 - b.0, a.1 - b.1, a.2 - b.2)
+}
+
+/// A 60° counterclockwise turn around the origin.
+///
+/// Turns and mirrors of cube coordinates only permute and negate `(q, r, s)`. Which
+/// permutation goes which way depends on `hexgridspiral`'s layout, so both are picked by
+/// checking them against `DIRECTION_OFFSETS`.
+static TURN_CCW: Lazy<fn(Cube) -> Cube> = Lazy::new(|| {
+    let turns: [fn(Cube) -> Cube; 2] = [|(q, r, s)| (-r, -s, -q), |(q, r, s)| (-s, -q, -r)];
+    turns
+        .into_iter()
+        .find(|turn| turn(HexDirection::East.offset()) == HexDirection::NorthEast.offset())
+        .expect("one of the cube turns is counterclockwise")
+});
+
+/// A mirror across the east-west axis through the origin.
+static MIRROR: Lazy<fn(Cube) -> Cube> = Lazy::new(|| {
+    let mirrors: [fn(Cube) -> Cube; 3] = [
+        |(q, r, s)| (-r, -q, -s),
+        |(q, r, s)| (-s, -r, -q),
+        |(q, r, s)| (-q, -s, -r),
+    ];
+    mirrors
+        .into_iter()
+        .find(|mirror| mirror(HexDirection::East.offset()) == HexDirection::East.offset())
+        .expect("one of the cube mirrors keeps east in place")
+});
+
+/// Turns a cube offset around the origin, counterclockwise by `steps` times 60°.
+pub fn rotate(cube: Cube, steps: u8) -> Cube {
+    (0..steps % 6).fold(cube, |cube, _| (*TURN_CCW)(cube))
+}
+
+/// Mirrors a cube offset across the east-west axis through the origin, so north and
+/// south swap.
+pub fn mirror(cube: Cube) -> Cube {
+    (*MIRROR)(cube)
+}
+
+/// Number of steps between two hexes.
+pub fn distance(a: Cube, b: Cube) -> i64 {
+    let d = subtract(a, b);
+    d.0.abs().max(d.1.abs()).max(d.2.abs())
+}
+
+/// The hexes on the straight line from `a` to `b`, both included, with no gaps.
+pub fn line(a: Cube, b: Cube) -> Vec<Cube> {
+    let steps = distance(a, b);
+    if steps == 0 {
+        return vec![a];
+    }
+    // Nudge the start off hex edges so points exactly between two hexes round consistently.
+    let start = (a.0 as f64 + 1e-6, a.1 as f64 + 2e-6, a.2 as f64 - 3e-6);
+    let end = (b.0 as f64, b.1 as f64, b.2 as f64);
+    (0..=steps)
+        .map(|step| {
+            let t = step as f64 / steps as f64;
+            round((
+                start.0 + (end.0 - start.0) * t,
+                start.1 + (end.1 - start.1) * t,
+                start.2 + (end.2 - start.2) * t,
+            ))
+        })
+        .collect()
+}
+
+/// The hex containing fractional cube coordinates.
+pub fn round(cube: (f64, f64, f64)) -> Cube {
+    let (mut q, mut r, mut s) = (cube.0.round(), cube.1.round(), cube.2.round());
+    let (dq, dr, ds) = ((q - cube.0).abs(), (r - cube.1).abs(), (s - cube.2).abs());
+    // Fix up the coordinate that was rounded the most, so that q + r + s stays 0.
+    if dq > dr && dq > ds {
+        q = -r - s;
+    } else if dr > ds {
+        r = -q - s;
+    } else {
+        s = -q - r;
+    }
+    (q as i64, r as i64, s as i64)
+}
+
+/// Every hex within `radius` steps of `centre`, including the centre itself.
+pub fn range(centre: Cube, radius: i64) -> Vec<Cube> {
+    let mut cubes = Vec::new();
+    for q in -radius..=radius {
+        for r in (-radius).max(-q - radius)..=radius.min(-q + radius) {
+            cubes.push(add(centre, (q, r, -q - r)));
+        }
+    }
+    cubes
+}
+
+/// The hexes exactly `radius` steps from `centre`, or just the centre for a radius of 0.
+pub fn ring(centre: Cube, radius: i64) -> Vec<Cube> {
+    if radius == 0 {
+        return vec![centre];
+    }
+    // Start at the south-west corner and walk each side counterclockwise.
+    let mut cube = add(centre, scale(HexDirection::SouthWest.offset(), radius));
+    let mut cubes = Vec::with_capacity(6 * radius as usize);
+    for direction in HexDirection::ALL {
+        for _ in 0..radius {
+            cubes.push(cube);
+            cube = add(cube, direction.offset());
+        }
+    }
+    cubes
+}
+
+/// Spiral indices of every hex within `radius` steps of `index`, including itself.
+pub fn within(index: hgs::TileIndex, radius: i64) -> impl Iterator<Item = hgs::TileIndex> {
+    range(cube(index), radius).into_iter().map(index_of)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_and_cube_round_trip() {
+        for cube_in in range((0, 0, 0), 6) {
+            assert_eq!(cube(index_of(cube_in)), cube_in);
+        }
+    }
+
+    #[test]
+    fn directions_point_at_distinct_neighbours() {
+        let offsets: Vec<Cube> = HexDirection::ALL.map(HexDirection::offset).into();
+        for (i, offset) in offsets.iter().enumerate() {
+            assert_eq!(distance((0, 0, 0), *offset), 1);
+            assert!(!offsets[i + 1..].contains(offset));
+        }
+    }
+
+    #[test]
+    fn range_covers_every_hex_within_the_radius() {
+        let centre = (2, -5, 3);
+        for radius in 0..6 {
+            let cubes = range(centre, radius);
+            assert_eq!(cubes.len() as i64, 3 * radius * (radius + 1) + 1);
+            assert!(cubes.iter().all(|cube| distance(centre, *cube) <= radius));
+            assert!(cubes.iter().all(|cube| cube.0 + cube.1 + cube.2 == 0));
+        }
+    }
+
+    #[test]
+    fn within_is_range_by_index() {
+        let centre = index_of((1, 1, -2));
+        let indices: Vec<hgs::TileIndex> = within(centre, 2).collect();
+        assert_eq!(indices.len(), 19);
+        assert!(
+            indices
+                .iter()
+                .all(|index| distance(cube(centre), cube(*index)) <= 2)
+        );
+    }
+
+    #[test]
+    fn line_joins_its_ends_without_gaps() {
+        let (a, b) = ((0, 0, 0), (5, -2, -3));
+        let cubes = line(a, b);
+        assert_eq!(cubes.first(), Some(&a));
+        assert_eq!(cubes.last(), Some(&b));
+        assert_eq!(cubes.len() as i64, distance(a, b) + 1);
+        assert!(cubes.windows(2).all(|pair| distance(pair[0], pair[1]) == 1));
+        assert_eq!(line(a, a), [a]);
+    }
+
+    #[test]
+    fn rotate_turns_each_direction_into_the_next() {
+        for direction in HexDirection::ALL {
+            let next = HexDirection::from_steps_ccw(direction.steps_ccw() + 1);
+            assert_eq!(rotate(direction.offset(), 1), next.offset());
+        }
+        let cube = (3, -1, -2);
+        assert_eq!(rotate(cube, 6), cube);
+        assert_eq!(rotate(rotate(cube, 2), 4), cube);
+    }
+
+    #[test]
+    fn mirror_swaps_north_and_south() {
+        use HexDirection::*;
+        assert_eq!(mirror(East.offset()), East.offset());
+        assert_eq!(mirror(West.offset()), West.offset());
+        assert_eq!(mirror(NorthEast.offset()), SouthEast.offset());
+        assert_eq!(mirror(NorthWest.offset()), SouthWest.offset());
+        let cube = (3, -1, -2);
+        assert_eq!(mirror(mirror(cube)), cube);
+    }
+
+    #[test]
+    fn ring_is_every_hex_at_the_radius() {
+        let centre = (1, -3, 2);
+        assert_eq!(ring(centre, 0), [centre]);
+        for radius in 1..6 {
+            let cubes = ring(centre, radius);
+            assert_eq!(cubes.len() as i64, 6 * radius);
+            assert!(cubes.iter().all(|cube| distance(centre, *cube) == radius));
+            let outer = range(centre, radius).len() - range(centre, radius - 1).len();
+            assert_eq!(cubes.len(), outer);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+use std::collections::HashMap;
+
+use crate::map::{HexData, HexMap};
+
+/// Maximum number of undo steps kept in memory.
+const MAX_UNDO_STEPS: usize = 200;
+
+/// A change to a single hex. `None` means the hex does not exist.
+#[derive(Debug, Clone, Copy)]
+pub struct TileChange {
+    pub index: hgs::TileIndex,
+    pub before: Option<HexData>,
+    pub after: Option<HexData>,
+}
+
+/// One undo step: every hex touched by a stroke, in the order it was first touched.
+#[derive(Debug, Default, Clone)]
+pub struct Edit {
+    changes: Vec<TileChange>,
+    positions: HashMap<hgs::TileIndex, usize>,
+    /// Variant seed before and after the step, if the step changed it.
+    seed: Option<(u64, u64)>,
+}
+
+impl Edit {
+    fn record(&mut self, index: hgs::TileIndex, before: Option<HexData>, after: Option<HexData>) {
+        // A hex painted several times in one stroke keeps its original `before`.
+        match self.positions.get(&index) {
+            Some(&position) => self.changes[position].after = after,
+            None => {
+                self.positions.insert(index, self.changes.len());
+                self.changes.push(TileChange {
+                    index,
+                    before,
+                    after,
+                });
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.seed.is_none_or(|(before, after)| before == after)
+            && self
+                .changes
+                .iter()
+                .all(|change| change.before == change.after)
+    }
+}
+
+/// Undo/redo stacks for map edits.
+///
+/// All edits should go through `apply` so they are recorded. Changes are collected into
+/// an open stroke until `end_stroke` is called, which turns them into a single undo step.
+#[derive(Resource, Default)]
+pub struct EditHistory {
+    undo_stack: Vec<Edit>,
+    redo_stack: Vec<Edit>,
+    stroke: Option<Edit>,
+    /// Hexes changed through `apply` since the last `take_touched`.
+    touched: Vec<hgs::TileIndex>,
+    /// Whether the last closed stroke was kept as the top undo step, rather than dropped
+    /// for making no net change. Only then may `amend` still add to it.
+    last_stroke_kept: bool,
+}
+
+impl EditHistory {
+    /// Sets (or with `None`, removes) a hex and records the change in the open stroke.
+    pub fn apply(&mut self, map: &mut HexMap, index: hgs::TileIndex, after: Option<HexData>) {
+        let before = map.get(index).copied();
+        if before == after {
+            return;
+        }
+        write(map, index, after);
+        self.stroke
+            .get_or_insert_with(Edit::default)
+            .record(index, before, after);
+        self.touched.push(index);
+    }
+
+    /// Records a follow-up change into the current step: the open stroke or, if the stroke
+    /// was already closed, the undo step it became. Used for automatic fix-ups of an edit,
+    /// which should be undone together with it. If that stroke was dropped for making no net
+    /// change, the follow-up opens a stroke of its own, which the caller closes.
+    /// Amended hexes are not reported by `take_touched`.
+    pub fn amend(&mut self, map: &mut HexMap, index: hgs::TileIndex, after: Option<HexData>) {
+        let before = map.get(index).copied();
+        if before == after {
+            return;
+        }
+        write(map, index, after);
+        let edit = match (&mut self.stroke, self.undo_stack.last_mut()) {
+            (Some(stroke), _) => stroke,
+            (None, Some(edit)) if self.last_stroke_kept => edit,
+            (stroke, _) => stroke.insert(Edit::default()),
+        };
+        edit.record(index, before, after);
+    }
+
+    /// Whether changes are being collected into a stroke that `end_stroke` has not closed yet.
+    pub fn stroke_open(&self) -> bool {
+        self.stroke.is_some()
+    }
+
+    /// Returns and clears the hexes edited through `apply` since the last call.
+    pub fn take_touched(&mut self) -> Vec<hgs::TileIndex> {
+        std::mem::take(&mut self.touched)
+    }
+
+    /// Replaces the whole map and its variant seed as a single undoable step.
+    pub fn replace_all(
+        &mut self,
+        map: &mut HexMap,
+        seed: u64,
+        tiles: impl IntoIterator<Item = (hgs::TileIndex, HexData)>,
+    ) {
+        self.end_stroke();
+        let tiles: HashMap<hgs::TileIndex, HexData> = tiles.into_iter().collect();
+        let removed: Vec<hgs::TileIndex> = map
+            .iter()
+            .map(|(index, _)| *index)
+            .filter(|index| !tiles.contains_key(index))
+            .collect();
+        for index in removed {
+            self.apply(map, index, None);
+        }
+        for (index, data) in tiles {
+            self.apply(map, index, Some(data));
+        }
+        if map.seed() != seed {
+            self.stroke.get_or_insert_with(Edit::default).seed = Some((map.seed(), seed));
+            map.set_seed(seed);
+        }
+        self.end_stroke();
+        // A loaded map is taken as is, without automatic fix-ups.
+        self.touched.clear();
+    }
+
+    /// Closes the open stroke, turning it into one undo step.
+    pub fn end_stroke(&mut self) {
+        let Some(edit) = self.stroke.take() else {
+            return;
+        };
+        self.last_stroke_kept = !edit.is_empty();
+        if !self.last_stroke_kept {
+            return;
+        }
+        self.undo_stack.push(edit);
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Reverts the most recent step. Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self, map: &mut HexMap) -> bool {
+        self.end_stroke();
+        // Fix-ups of an undone stroke are undone with it.
+        self.touched.clear();
+        self.last_stroke_kept = false;
+        let Some(edit) = self.undo_stack.pop() else {
+            return false;
+        };
+        for change in edit.changes.iter().rev() {
+            write(map, change.index, change.before);
+        }
+        if let Some((before, _)) = edit.seed {
+            map.set_seed(before);
+        }
+        self.redo_stack.push(edit);
+        true
+    }
+
+    /// Re-applies the most recently undone step. Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self, map: &mut HexMap) -> bool {
+        self.end_stroke();
+        self.touched.clear();
+        self.last_stroke_kept = false;
+        let Some(edit) = self.redo_stack.pop() else {
+            return false;
+        };
+        for change in &edit.changes {
+            write(map, change.index, change.after);
+        }
+        if let Some((_, after)) = edit.seed {
+            map.set_seed(after);
+        }
+        self.undo_stack.push(edit);
+        true
+    }
+}
+
+fn write(map: &mut HexMap, index: hgs::TileIndex, data: Option<HexData>) {
+    match data {
+        Some(data) => {
+            map.set(index, data);
+        }
+        None => {
+            map.remove(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_loading::AssetTag;
+
+    fn index(value: u64) -> hgs::TileIndex {
+        hgs::TileIndex::from(value)
+    }
+
+    fn tag_at(map: &HexMap, value: u64) -> Option<AssetTag> {
+        map.get(index(value)).map(|data| data.tag)
+    }
+
+    #[test]
+    fn stroke_is_one_undo_step() {
+        let mut map = HexMap::default();
+        let mut history = EditHistory::default();
+        history.apply(&mut map, index(1), Some(HexData::new(AssetTag::PlainsLush)));
+        history.apply(&mut map, index(2), Some(HexData::new(AssetTag::PlainsLush)));
+        history.end_stroke();
+
+        assert!(history.undo(&mut map));
+        assert!(map.is_empty());
+        assert!(history.redo(&mut map));
+        assert_eq!(tag_at(&map, 2), Some(AssetTag::PlainsLush));
+        assert!(!history.redo(&mut map));
+    }
+
+    #[test]
+    fn replace_all_undoes_the_seed() {
+        let mut map = HexMap::default();
+        map.set_seed(1);
+        let mut history = EditHistory::default();
+        history.replace_all(&mut map, 2, [(index(1), HexData::new(AssetTag::Blank))]);
+        assert_eq!(map.seed(), 2);
+
+        assert!(history.undo(&mut map));
+        assert_eq!(map.seed(), 1);
+        assert!(map.is_empty());
+        assert!(history.redo(&mut map));
+        assert_eq!(map.seed(), 2);
+
+        // A new seed alone is still a step of its own.
+        history.replace_all(&mut map, 3, [(index(1), HexData::new(AssetTag::Blank))]);
+        assert!(history.undo(&mut map));
+        assert_eq!(map.seed(), 2);
+    }
+
+    #[test]
+    fn repainting_keeps_the_variant() {
+        let locked = HexData {
+            variant: Some(2),
+            ..HexData::new(AssetTag::PlainsLush)
+        };
+        let painted = HexData::new(AssetTag::PlainsLush);
+        assert_eq!(painted.painted_over(Some(&locked)), locked);
+        let other = HexData::new(AssetTag::HillsLush);
+        assert_eq!(other.painted_over(Some(&locked)), other);
+        let relocked = HexData {
+            variant: Some(1),
+            ..painted
+        };
+        assert_eq!(relocked.painted_over(Some(&locked)), relocked);
+        assert_eq!(painted.painted_over(None), painted);
+    }
+
+    #[test]
+    fn amend_joins_the_stroke_it_follows() {
+        let mut map = HexMap::default();
+        let mut history = EditHistory::default();
+        history.apply(&mut map, index(1), Some(HexData::new(AssetTag::PlainsLush)));
+        history.end_stroke();
+        history.amend(
+            &mut map,
+            index(2),
+            Some(HexData::new(AssetTag::CoastSmallE)),
+        );
+        assert!(!history.stroke_open());
+
+        assert!(history.undo(&mut map));
+        assert!(map.is_empty());
+        assert!(!history.undo(&mut map));
+    }
+
+    #[test]
+    fn amend_after_a_dropped_stroke_is_its_own_step() {
+        let mut map = HexMap::default();
+        let mut history = EditHistory::default();
+        history.apply(&mut map, index(1), Some(HexData::new(AssetTag::PlainsLush)));
+        history.end_stroke();
+
+        // A -> B -> A makes no net change, so the stroke is dropped.
+        history.apply(&mut map, index(1), Some(HexData::new(AssetTag::HillsLush)));
+        history.apply(&mut map, index(1), Some(HexData::new(AssetTag::PlainsLush)));
+        history.end_stroke();
+        history.amend(
+            &mut map,
+            index(2),
+            Some(HexData::new(AssetTag::CoastSmallE)),
+        );
+        assert!(history.stroke_open());
+        history.end_stroke();
+
+        assert!(history.undo(&mut map));
+        assert_eq!(tag_at(&map, 1), Some(AssetTag::PlainsLush));
+        assert_eq!(tag_at(&map, 2), None);
+    }
+}
@@ -0,0 +1,17 @@
+use wasm_bindgen::JsValue;
+
+/// Dispatches a `CustomEvent` on `window` so the page can react to state changes in Rust.
+/// `detail` is available to listeners as `event.detail`.
+pub fn dispatch(name: &str, detail: &JsValue) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let init = web_sys::CustomEventInit::new();
+    init.set_detail(detail);
+    match web_sys::CustomEvent::new_with_event_init_dict(name, &init) {
+        Ok(event) => {
+            let _ = window.dispatch_event(&event);
+        }
+        Err(err) => log::warn!("Could not create {} event: {:?}", name, err),
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+use strum_macros::{EnumString, IntoStaticStr};
+
+use crate::hex;
+use crate::layout::Orientation;
+
+/// Which coordinates tile labels show.
+#[derive(EnumString, IntoStaticStr, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum LabelMode {
+    /// The `hexgridspiral` spiral index, e.g. `37`.
+    #[default]
+    Spiral,
+    /// Cube coordinates, e.g. `2, -1, -1`.
+    Cube,
+    /// Axial coordinates, e.g. `2, -1`.
+    Axial,
+    /// Column and row as on printed hexcrawl maps, e.g. `0412`. The top-left hex of the map
+    /// is `0101`.
+    Offset,
+}
+
+/// How tile labels are written.
+#[derive(Resource, Default)]
+pub struct LabelFormat {
+    pub mode: LabelMode,
+    /// Doubled coordinates of the map's top-left corner, numbered `0101`; see
+    /// `HexMap::offset_origin`.
+    pub offset_origin: (i64, i64),
+}
+
+impl LabelFormat {
+    pub fn text(&self, index: hgs::TileIndex, orientation: Orientation) -> String {
+        let cube = hex::cube(index);
+        match self.mode {
+            LabelMode::Spiral => format!("{}", index),
+            LabelMode::Cube => format!("{}, {}, {}", cube.0, cube.1, cube.2),
+            LabelMode::Axial => format!("{}, {}", cube.0, cube.1),
+            LabelMode::Offset => {
+                let (column, row) = orientation.offset_coords(cube, self.offset_origin);
+                format!("{:02}{:02}", column, row)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_loading::AssetTag;
+    use crate::map::{HexData, HexMap};
+    use crate::map_shape::MapShape;
+    use std::collections::HashSet;
+
+    #[test]
+    fn offset_labels_number_rectangles_from_0101() {
+        for orientation in [Orientation::Flat, Orientation::Pointy] {
+            let mut origin_parities = HashSet::new();
+            for (width, height) in [(2, 3), (3, 2), (4, 5), (5, 4)] {
+                let mut map = HexMap::default();
+                map.set_orientation(orientation);
+                let shape = MapShape::Rectangle { width, height };
+                for index in shape.indices(orientation) {
+                    map.set(index, HexData::new(AssetTag::Blank));
+                }
+                let format = LabelFormat {
+                    mode: LabelMode::Offset,
+                    offset_origin: map.offset_origin(),
+                };
+                let shifted = match orientation {
+                    Orientation::Flat => format.offset_origin.0,
+                    Orientation::Pointy => format.offset_origin.1,
+                };
+                origin_parities.insert(shifted.rem_euclid(2));
+
+                let labels: HashSet<String> = map
+                    .iter()
+                    .map(|(index, _)| format.text(*index, orientation))
+                    .collect();
+                let expected: HashSet<String> = (1..=width)
+                    .flat_map(|column| {
+                        (1..=height).map(move |row| format!("{:02}{:02}", column, row))
+                    })
+                    .collect();
+                assert_eq!(labels, expected, "{:?} {}x{}", orientation, width, height);
+            }
+            assert_eq!(
+                origin_parities.len(),
+                2,
+                "both odd and even origins are covered"
+            );
+        }
+    }
+}
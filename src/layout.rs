@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+use serde::{Deserialize, Serialize};
+use strum_macros::{EnumString, FromRepr, IntoStaticStr};
+
+use crate::asset_loading::TileFacing;
+use crate::hex::{Cube, HexDirection};
+use crate::tile_config::step_size;
+
+/// How hexes sit on screen.
+///
+/// The tile art and `hexgridspiral` pixel coordinates are pointy-top. Flat-top maps are
+/// drawn by rotating both tile positions and tile art 90° counterclockwise around the origin.
+/// Drawing and picking both go through this type.
+#[derive(
+    Serialize,
+    Deserialize,
+    EnumString,
+    IntoStaticStr,
+    FromRepr,
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+#[repr(u8)]
+pub enum Orientation {
+    #[default]
+    Flat,
+    Pointy,
+}
+
+impl Orientation {
+    /// Rotation from the pointy-top art frame to the screen.
+    pub fn rotation(self) -> Quat {
+        match self {
+            Orientation::Flat => Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+            Orientation::Pointy => Quat::IDENTITY,
+        }
+    }
+
+    /// Rotation of a tile's art: the map rotation plus the tile's own facing.
+    pub fn tile_rotation(self, facing: TileFacing) -> Quat {
+        self.rotation() * facing.rotation()
+    }
+
+    /// World position of a hex centre.
+    pub fn world_position(self, cube: Cube) -> Vec2 {
+        let (x, y) =
+            hgs::CCTile::from_qrs(cube.0, cube.1, cube.2).to_irregular_pixel((0., 0.), step_size());
+        let (x, y) = (x as f32, y as f32);
+        match self {
+            // Rotate position 90° CCW around origin (0, 0)
+            Orientation::Flat => Vec2::new(-y, x),
+            Orientation::Pointy => Vec2::new(x, y),
+        }
+    }
+
+    /// The hex under a world position, whether or not it exists in the map.
+    pub fn hex_at(self, world: Vec2) -> hgs::TileIndex {
+        let (x, y) = (world.x as f64, world.y as f64);
+        let pixel = match self {
+            // Undo the 90° CCW rotation.
+            Orientation::Flat => (y, -x),
+            Orientation::Pointy => (x, y),
+        };
+        let tile: hgs::HGSTile =
+            hgs::CCTile::from_irregular_pixel(pixel, (0., 0.), step_size()).into();
+        tile.spiral_index()
+    }
+
+    /// Doubled coordinates `(column, row)` of a hex, counting columns left to right and rows
+    /// top to bottom, as printed hexcrawl maps do. Every other column (flat-top) or row
+    /// (pointy-top) is shifted by half a hex, so the other coordinate counts half hexes: it
+    /// is odd exactly in the shifted columns (rows). The origin hex is at `(0, 0)`.
+    pub fn doubled_coords(self, cube: Cube) -> (i64, i64) {
+        let position = self.world_position(cube);
+        // Spacing of columns and of hexes within a column (flat-top), or of rows and of
+        // hexes within a row (pointy-top).
+        let across = self.world_position(HexDirection::NorthEast.offset());
+        let along = self.world_position(HexDirection::East.offset());
+        match self {
+            Orientation::Flat => {
+                let column = (position.x / across.x.abs()).round() as i64;
+                let row = (-2. * position.y / along.y.abs()).round() as i64;
+                (column, row)
+            }
+            Orientation::Pointy => {
+                let row = (-position.y / across.y.abs()).round() as i64;
+                let column = (2. * position.x / along.x.abs()).round() as i64;
+                (column, row)
+            }
+        }
+    }
+
+    /// Offset coordinates `(column, row)` of a hex, counted from 1 at `origin`, the doubled
+    /// coordinates of the leftmost column and the topmost hex of the map.
+    ///
+    /// Rows (columns) are counted from the map's topmost (leftmost) hex, and a hex half a hex
+    /// below (right of) it is still in the first one. Which columns (rows) are shifted thus
+    /// follows from their absolute parity, and a rectangular map starts with a full row
+    /// whether its leftmost column is odd or even.
+    pub fn offset_coords(self, cube: Cube, origin: (i64, i64)) -> (i64, i64) {
+        let (column, row) = self.doubled_coords(cube);
+        match self {
+            Orientation::Flat => (column - origin.0 + 1, (row - origin.1).div_euclid(2) + 1),
+            Orientation::Pointy => ((column - origin.0).div_euclid(2) + 1, row - origin.1 + 1),
+        }
+    }
+}
+
+/// Depth of a tile at rest, so that lower tiles overlap the ones above them.
+pub fn tile_depth(screen_y: f32) -> f32 {
+    screen_y * -0.0001
+}
@@ -1,32 +1,130 @@
 mod asset_loading;
+mod autocoast;
+mod autosave;
+mod chunks;
+mod clipboard;
 mod components;
+mod fill;
+mod hex;
+mod history;
+mod js_events;
+mod labels;
+mod layout;
+mod map;
+mod map_document;
+mod map_link;
+mod map_shape;
+mod random;
 mod resources;
+mod symmetry;
 mod systems;
 mod tile_config;
 
 use bevy::{asset::AssetMetaCheck, input::mouse::MouseWheel, prelude::*};
+use clipboard::Clipboard;
+use hexgridspiral as hgs;
+use history::EditHistory;
+use labels::{LabelFormat, LabelMode};
+use layout::Orientation;
+use map::HexMap;
+use map_document::MapDocument;
+use map_shape::MapShape;
 use once_cell::sync::Lazy;
-use resources::{HoveredTile, SelectedHex, WorldCoords};
+use resources::{
+    ActivePaintTool, Brush, ChunkMeshes, FillSettings, HoveredTile, MAX_BRUSH_RADIUS, PaintTool,
+    PreviewHexes, ScatterSettings, SelectShape, SelectedHex, Selection, SelectionSettings,
+    WorldCoords,
+};
+use serde::Deserialize;
+use std::collections::HashSet;
 use std::sync::Mutex;
+use symmetry::{ROTATION_COUNTS, Symmetry};
 use systems::{
-    cursor::cursor_system,
+    autocoast::{
+        SetAutoCoastEvent, auto_coast_system, flush_auto_coast_queue, handle_set_auto_coast_event,
+    },
+    autosave::autosave_system,
+    catalog::apply_tile_catalog,
+    chunks::{VisibleChunks, chunk_visibility_system},
+    clipboard::{
+        ClipboardEvent, clipboard_keyboard_system, flush_clipboard_queue, handle_clipboard_event,
+    },
+    cursor::{cursor_system, hover_highlight_system},
+    history::{HistoryEvent, flush_history_queue, handle_history_event, history_keyboard_system},
+    map_io::{
+        ImportMapEvent, flush_map_import_queue, handle_import_map_event, publish_map_snapshot,
+    },
+    map_shape::{MapShapeEvent, flush_map_shape_queue, handle_map_shape_event},
+    paint::paint_system,
+    preview::preview_system,
+    scatter::{ScatterEvent, flush_scatter_queue, handle_scatter_event, scatter_system},
+    selection::{
+        SelectionEvent, flush_selection_queue, handle_selection_event, publish_selection_snapshot,
+        select_system, selection_highlight_system, selection_keyboard_system,
+    },
     setup::setup,
-    tools::{HexSelectedEvent, flush_click_events_system, on_hex_selected},
+    symmetry::{
+        SymmetryEvent, flush_symmetry_queue, handle_symmetry_event, symmetry_keyboard_system,
+    },
+    sync::{ChunkChangedEvent, sync_tiles_system},
+    tools::{
+        HexSelectedEvent, SetBrushRadiusEvent, SetFillLimitEvent, SetPaintToolEvent,
+        brush_keyboard_system, eyedropper_system, flush_brush_queue, flush_click_events_system,
+        flush_paint_tool_queue, notify_tile_selected, on_brush_radius_set, on_hex_selected,
+        on_paint_tool_set, paint_tool_keyboard_system,
+    },
+    variants::variant_keyboard_system,
 };
 use wasm_bindgen::prelude::*;
 
 use crate::{
-    asset_loading::AssetTag,
-    resources::ShowTileLabels,
+    asset_loading::{AssetTag, Biome, TileCatalog, TileCatalogLoader},
+    resources::{AutoCoast, ShowTileLabels},
     systems::labels::{
-        ToggleTileLabelsEvent, flush_tile_label_toggle_queue, handle_toggle_tile_labels_event,
-        toggle_tile_labels_system,
+        SetLabelModeEvent, ToggleTileLabelsEvent, flush_label_mode_queue,
+        flush_tile_label_toggle_queue, handle_set_label_mode_event,
+        handle_toggle_tile_labels_event, tile_label_system,
     },
 };
 
 /// Global queue used to forward tool events from JavaScript to Bevy.
 pub static TOOL_QUEUE: Lazy<Mutex<Vec<HexSelectedEvent>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Brush radius changes coming from the UI.
+pub static BRUSH_QUEUE: Lazy<Mutex<Vec<u8>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Paint tool changes coming from the UI.
+pub static PAINT_TOOL_QUEUE: Lazy<Mutex<Vec<PaintTool>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Fill limit changes coming from the UI.
+pub static FILL_LIMIT_QUEUE: Lazy<Mutex<Vec<Option<u32>>>> = Lazy::new(|| Mutex::new(Vec::new()));
 pub static LABEL_QUEUE: Lazy<Mutex<Vec<()>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Copy, paste and stamp requests coming from the UI.
+pub static CLIPBOARD_QUEUE: Lazy<Mutex<Vec<ClipboardEvent>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Selection shape changes and bulk actions coming from the UI.
+pub static SELECTION_QUEUE: Lazy<Mutex<Vec<SelectionEvent>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Scatter brush changes coming from the UI.
+pub static SCATTER_QUEUE: Lazy<Mutex<Vec<ScatterEvent>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Symmetric painting changes coming from the UI.
+pub static SYMMETRY_QUEUE: Lazy<Mutex<Vec<SymmetryEvent>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Label mode changes coming from the UI.
+pub static LABEL_MODE_QUEUE: Lazy<Mutex<Vec<LabelMode>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Maps parsed by `import_map`, waiting to replace the current map.
+pub static IMPORT_QUEUE: Lazy<Mutex<Vec<MapDocument>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Undo/redo requests coming from the UI buttons.
+pub static HISTORY_QUEUE: Lazy<Mutex<Vec<HistoryEvent>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// New map and resize requests from the "new map" dialog.
+pub static MAP_SHAPE_QUEUE: Lazy<Mutex<Vec<MapShapeEvent>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Auto-coast on/off switches coming from the UI.
+pub static AUTO_COAST_QUEUE: Lazy<Mutex<Vec<bool>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// The loaded tile catalog, read by `list_tiles`. `None` until the catalog asset has loaded.
+pub static TILE_CATALOG_SNAPSHOT: Lazy<Mutex<Option<TileCatalog>>> = Lazy::new(|| Mutex::new(None));
+/// Latest copy of the map, kept current by `publish_map_snapshot` for `export_map`.
+/// While a stroke is painted it holds the map from before the stroke.
+pub static MAP_SNAPSHOT: Lazy<Mutex<MapDocument>> =
+    Lazy::new(|| Mutex::new(MapDocument::default()));
+
+/// Latest copy of the selection, kept current by `publish_selection_snapshot` for
+/// `export_selection`.
+pub static SELECTION_SNAPSHOT: Lazy<Mutex<HashSet<hgs::TileIndex>>> =
+    Lazy::new(|| Mutex::new(HashSet::new()));
 
 /// External JavaScript function used for logging (when compiled to WebAssembly).
 #[wasm_bindgen]
@@ -34,25 +132,407 @@ extern "C" {
     fn log(s: &str);
 }
 
-/// Called from JavaScript to set the current tool.
+/// Called from JavaScript to set the current tool, using a tile id from `list_tiles`.
+#[wasm_bindgen]
+pub fn set_tile(tile_id: &str) -> Result<(), JsValue> {
+    log::warn!("Tool selected in Rust: {}", tile_id);
+    let tag: AssetTag = tile_id
+        .parse()
+        .map_err(|_| JsValue::from_str(&format!("Unknown tile id: {}", tile_id)))?;
+    TOOL_QUEUE.lock().unwrap().push(HexSelectedEvent(tag));
+    Ok(())
+}
+
+/// Called from JavaScript to get the toolbox palette: groups of tiles with their
+/// ids, display names and thumbnail paths, the eraser first. Returns `null` until the
+/// tile catalog has loaded; the page is notified with a `tilecatalogloaded` event once
+/// it is available.
+#[wasm_bindgen]
+pub fn list_tiles() -> JsValue {
+    let snapshot = TILE_CATALOG_SNAPSHOT.lock().unwrap();
+    match snapshot.as_ref() {
+        Some(catalog) => serde_wasm_bindgen::to_value(&catalog.palette())
+            .expect("palette is always serializable"),
+        None => JsValue::NULL,
+    }
+}
+
+/// Called from JavaScript to set how many steps around the hovered hex get painted, 0 to 5.
+#[wasm_bindgen]
+pub fn set_brush_radius(radius: u8) -> Result<(), JsValue> {
+    if radius > MAX_BRUSH_RADIUS {
+        return Err(JsValue::from_str(&format!(
+            "Brush radius must be at most {}",
+            MAX_BRUSH_RADIUS
+        )));
+    }
+    BRUSH_QUEUE.lock().unwrap().push(radius);
+    Ok(())
+}
+
+/// Called from JavaScript to choose what clicking the map does: `"brush"`, `"fill"`,
+/// `"line"`, `"ring"`, `"hexagon"`, `"select"`, `"paste"` or `"scatter"`.
+#[wasm_bindgen]
+pub fn set_paint_tool(tool: &str) -> Result<(), JsValue> {
+    let tool: PaintTool = tool
+        .parse()
+        .map_err(|_| JsValue::from_str(&format!("Unknown paint tool: {}", tool)))?;
+    PAINT_TOOL_QUEUE.lock().unwrap().push(tool);
+    Ok(())
+}
+
+/// One tile of the scatter palette, as passed to `set_scatter_palette`.
+#[derive(Deserialize)]
+struct ScatterEntry {
+    id: String,
+    weight: u32,
+}
+
+/// Called from JavaScript to set the tiles the scatter brush picks from, with relative
+/// weights, e.g. `[{ id: "ForestConiferLush", weight: 60 }, { id: "HillsLush", weight: 10 }]`.
+#[wasm_bindgen]
+pub fn set_scatter_palette(entries: JsValue) -> Result<(), JsValue> {
+    let entries: Vec<ScatterEntry> = serde_wasm_bindgen::from_value(entries)?;
+    if entries.is_empty() {
+        return Err(JsValue::from_str("Scatter palette must not be empty"));
+    }
+    let palette = entries
+        .into_iter()
+        .map(|entry| {
+            let tag: AssetTag = entry
+                .id
+                .parse()
+                .map_err(|_| JsValue::from_str(&format!("Unknown tile id: {}", entry.id)))?;
+            if entry.weight == 0 {
+                return Err(JsValue::from_str(&format!(
+                    "Weight of {} must be positive",
+                    entry.id
+                )));
+            }
+            Ok((tag, entry.weight))
+        })
+        .collect::<Result<Vec<_>, JsValue>>()?;
+    SCATTER_QUEUE
+        .lock()
+        .unwrap()
+        .push(ScatterEvent::SetPalette(palette));
+    Ok(())
+}
+
+/// Called from JavaScript to set the percentage (0 to 100) of hexes under the scatter
+/// brush that get painted.
+#[wasm_bindgen]
+pub fn set_scatter_density(percent: u8) -> Result<(), JsValue> {
+    if percent > 100 {
+        return Err(JsValue::from_str("Scatter density must be at most 100"));
+    }
+    SCATTER_QUEUE
+        .lock()
+        .unwrap()
+        .push(ScatterEvent::SetDensity(percent));
+    Ok(())
+}
+
+/// Called from JavaScript to paint symmetrically: mirrored across the horizontal and/or
+/// vertical line through the symmetry centre, and in `rotations` (1, 2, 3 or 6) copies
+/// turned evenly around it.
+#[wasm_bindgen]
+pub fn set_symmetry(
+    mirror_horizontal: bool,
+    mirror_vertical: bool,
+    rotations: u8,
+) -> Result<(), JsValue> {
+    if !ROTATION_COUNTS.contains(&rotations) {
+        return Err(JsValue::from_str(&format!(
+            "Rotations must be one of {:?}",
+            ROTATION_COUNTS
+        )));
+    }
+    SYMMETRY_QUEUE.lock().unwrap().push(SymmetryEvent::Set {
+        mirror_horizontal,
+        mirror_vertical,
+        rotations,
+    });
+    Ok(())
+}
+
+/// Called from JavaScript to move the symmetry centre back to the origin hex.
+/// `C` on the map moves it to the hovered hex instead.
+#[wasm_bindgen]
+pub fn reset_symmetry_centre() {
+    SYMMETRY_QUEUE
+        .lock()
+        .unwrap()
+        .push(SymmetryEvent::SetCentre(None));
+}
+
+/// Called from JavaScript to choose how the select tool picks hexes:
+/// `"brush"`, `"rectangle"` or `"lasso"`.
+#[wasm_bindgen]
+pub fn set_select_shape(shape: &str) -> Result<(), JsValue> {
+    let shape: SelectShape = shape
+        .parse()
+        .map_err(|_| JsValue::from_str(&format!("Unknown select shape: {}", shape)))?;
+    SELECTION_QUEUE
+        .lock()
+        .unwrap()
+        .push(SelectionEvent::SetShape(shape));
+    Ok(())
+}
+
+/// Called from JavaScript to deselect every hex.
+#[wasm_bindgen]
+pub fn clear_selection() {
+    SELECTION_QUEUE.lock().unwrap().push(SelectionEvent::Clear);
+}
+
+/// Called from JavaScript to paint every selected hex with the selected tile.
+#[wasm_bindgen]
+pub fn repaint_selection() {
+    SELECTION_QUEUE
+        .lock()
+        .unwrap()
+        .push(SelectionEvent::Repaint);
+}
+
+/// Called from JavaScript to remove the selected hexes from the map.
+#[wasm_bindgen]
+pub fn erase_selection() {
+    SELECTION_QUEUE.lock().unwrap().push(SelectionEvent::Erase);
+}
+
+/// Called from JavaScript to redraw the selected hexes for another biome:
+/// `"lush"`, `"damp"`, `"desert"`, `"rocky"` or `"snowy"`. Hexes without art for that
+/// biome are left as they are.
+#[wasm_bindgen]
+pub fn swap_selection_biome(biome: &str) -> Result<(), JsValue> {
+    let biome: Biome = biome
+        .parse()
+        .map_err(|_| JsValue::from_str(&format!("Unknown biome: {}", biome)))?;
+    SELECTION_QUEUE
+        .lock()
+        .unwrap()
+        .push(SelectionEvent::SwapBiome(biome));
+    Ok(())
+}
+
+/// Called from JavaScript to save just the selected hexes as a versioned JSON map,
+/// which loads like any other map file.
+#[wasm_bindgen]
+pub fn export_selection() -> String {
+    let selection = SELECTION_SNAPSHOT.lock().unwrap();
+    let mut document = MAP_SNAPSHOT.lock().unwrap().clone();
+    document
+        .tiles
+        .retain(|record| selection.contains(&hgs::TileIndex::from(record.index)));
+    document.to_json()
+}
+
+/// Called from JavaScript to copy the selected hexes into the clipboard.
+#[wasm_bindgen]
+pub fn copy_selection() {
+    CLIPBOARD_QUEUE.lock().unwrap().push(ClipboardEvent::Copy);
+}
+
+/// Called from JavaScript to turn the pasted pattern by `steps` times 60°:
+/// positive is counterclockwise, negative clockwise.
+#[wasm_bindgen]
+pub fn rotate_clipboard(steps: i32) {
+    let steps = steps.rem_euclid(6) as u8;
+    CLIPBOARD_QUEUE
+        .lock()
+        .unwrap()
+        .push(ClipboardEvent::Rotate(steps));
+}
+
+/// Called from JavaScript to mirror the pasted pattern north to south.
+#[wasm_bindgen]
+pub fn mirror_clipboard() {
+    CLIPBOARD_QUEUE.lock().unwrap().push(ClipboardEvent::Mirror);
+}
+
+/// Called from JavaScript to save the clipboard, as currently turned, as a named stamp.
+#[wasm_bindgen]
+pub fn save_stamp(name: &str) -> Result<(), JsValue> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(JsValue::from_str("Stamp name must not be empty"));
+    }
+    CLIPBOARD_QUEUE
+        .lock()
+        .unwrap()
+        .push(ClipboardEvent::SaveStamp(name.to_string()));
+    Ok(())
+}
+
+/// Called from JavaScript to load a saved stamp into the clipboard and start pasting it.
+#[wasm_bindgen]
+pub fn use_stamp(name: &str) -> Result<(), JsValue> {
+    if !clipboard::read_stamps().contains_key(name) {
+        return Err(JsValue::from_str(&format!("No stamp named {}", name)));
+    }
+    CLIPBOARD_QUEUE
+        .lock()
+        .unwrap()
+        .push(ClipboardEvent::UseStamp(name.to_string()));
+    Ok(())
+}
+
+/// Called from JavaScript to delete a saved stamp.
+#[wasm_bindgen]
+pub fn delete_stamp(name: &str) {
+    CLIPBOARD_QUEUE
+        .lock()
+        .unwrap()
+        .push(ClipboardEvent::DeleteStamp(name.to_string()));
+}
+
+/// Called from JavaScript to get the names of the saved stamps, sorted.
+#[wasm_bindgen]
+pub fn list_stamps() -> JsValue {
+    let names: Vec<String> = clipboard::read_stamps().into_keys().collect();
+    serde_wasm_bindgen::to_value(&names).expect("stamp names are always serializable")
+}
+
+/// Called from JavaScript to limit how many steps from the clicked hex a fill may reach.
+/// `undefined` removes the limit.
+#[wasm_bindgen]
+pub fn set_fill_limit(max_distance: Option<u32>) {
+    FILL_LIMIT_QUEUE.lock().unwrap().push(max_distance);
+}
+
+/// Called from JavaScript to undo the last map edit.
+#[wasm_bindgen]
+pub fn undo() {
+    HISTORY_QUEUE.lock().unwrap().push(HistoryEvent::Undo);
+}
+
+/// Called from JavaScript to redo the last undone map edit.
+#[wasm_bindgen]
+pub fn redo() {
+    HISTORY_QUEUE.lock().unwrap().push(HistoryEvent::Redo);
+}
+
+/// Called from JavaScript to save the current map as versioned JSON.
+#[wasm_bindgen]
+pub fn export_map() -> String {
+    MAP_SNAPSHOT.lock().unwrap().to_json()
+}
+
+/// Called from JavaScript to replace the current map with a previously exported one.
+#[wasm_bindgen]
+pub fn import_map(json: &str) -> Result<(), JsValue> {
+    let document = MapDocument::from_json(json).map_err(|err| JsValue::from_str(&err))?;
+    IMPORT_QUEUE.lock().unwrap().push(document);
+    Ok(())
+}
+
+/// Called from JavaScript to start over with blank hexes, e.g. `{ shape: "hexagon", radius: 4 }`,
+/// `{ shape: "rectangle", width: 12, height: 10 }` or `{ shape: "parallelogram", ... }`.
+#[wasm_bindgen]
+pub fn new_map(shape: JsValue) -> Result<(), JsValue> {
+    let shape = parse_map_shape(shape)?;
+    MAP_SHAPE_QUEUE
+        .lock()
+        .unwrap()
+        .push(MapShapeEvent::New(shape));
+    Ok(())
+}
+
+/// Called from JavaScript to grow or crop the map to a shape (see `new_map`).
+/// Painted hexes inside the shape are kept; missing hexes are added blank.
+#[wasm_bindgen]
+pub fn resize_map(shape: JsValue) -> Result<(), JsValue> {
+    let shape = parse_map_shape(shape)?;
+    MAP_SHAPE_QUEUE
+        .lock()
+        .unwrap()
+        .push(MapShapeEvent::Resize(shape));
+    Ok(())
+}
+
+/// Called from JavaScript to draw the map with `"flat"` or `"pointy"` top hexes.
+#[wasm_bindgen]
+pub fn set_orientation(orientation: &str) -> Result<(), JsValue> {
+    let orientation: Orientation = orientation
+        .parse()
+        .map_err(|_| JsValue::from_str(&format!("Unknown orientation: {}", orientation)))?;
+    MAP_SHAPE_QUEUE
+        .lock()
+        .unwrap()
+        .push(MapShapeEvent::SetOrientation(orientation));
+    Ok(())
+}
+
+fn parse_map_shape(shape: JsValue) -> Result<MapShape, JsValue> {
+    let shape: MapShape = serde_wasm_bindgen::from_value(shape)?;
+    shape.validate().map_err(|err| JsValue::from_str(&err))?;
+    Ok(shape)
+}
+
+/// Called from JavaScript to put the current map into the page URL; returns the shareable link.
+#[wasm_bindgen]
+pub fn share_map_link() -> Result<String, JsValue> {
+    map_link::write_to_location(&MAP_SNAPSHOT.lock().unwrap())
+}
+
+/// Called from JavaScript to load a map from a `#map=...` URL fragment.
 #[wasm_bindgen]
-pub fn set_tile(tile: &str) {
-    log::warn!("Tool selected in Rust: {}", tile);
-    let event = HexSelectedEvent(tile.to_string());
-    TOOL_QUEUE.lock().unwrap().push(event);
+pub fn load_map_link(fragment: &str) -> Result<(), JsValue> {
+    let document = map_link::parse_fragment(fragment)
+        .ok_or_else(|| JsValue::from_str("Link does not contain a map"))?
+        .map_err(|err| JsValue::from_str(&err))?;
+    IMPORT_QUEUE.lock().unwrap().push(document);
+    Ok(())
+}
+
+/// Called from JavaScript to list the autosaved snapshots (newest first) as JSON.
+#[wasm_bindgen]
+pub fn list_autosaves() -> String {
+    let summaries: Vec<autosave::AutosaveSummary> = autosave::list_autosaves()
+        .into_iter()
+        .map(|(slot, entry)| entry.summary(slot))
+        .collect();
+    serde_json::to_string(&summaries).expect("autosave summaries are always serializable")
+}
+
+/// Called from JavaScript to roll the map back to an autosaved snapshot.
+#[wasm_bindgen]
+pub fn restore_autosave(slot: usize) -> Result<(), JsValue> {
+    let entry = autosave::read_autosave(slot)
+        .ok_or_else(|| JsValue::from_str(&format!("No autosave in slot {}", slot)))?;
+    IMPORT_QUEUE.lock().unwrap().push(entry.map);
+    Ok(())
 }
 
 /// Called from JavaScript to toggle text labels
 #[wasm_bindgen]
-pub fn set_show_tile_labels(value: bool) {
+pub fn set_show_tile_labels(_value: bool) {
     LABEL_QUEUE.lock().unwrap().push(());
 }
 
+/// Called from JavaScript to turn automatic beach placement along water on or off.
+#[wasm_bindgen]
+pub fn set_auto_coast(enabled: bool) {
+    AUTO_COAST_QUEUE.lock().unwrap().push(enabled);
+}
+
+/// Called from JavaScript to choose the tile label coordinates:
+/// `"spiral"`, `"cube"`, `"axial"` or `"offset"` (column/row like `0412`).
+#[wasm_bindgen]
+pub fn set_label_mode(mode: &str) -> Result<(), JsValue> {
+    let mode: LabelMode = mode
+        .parse()
+        .map_err(|_| JsValue::from_str(&format!("Unknown label mode: {}", mode)))?;
+    LABEL_MODE_QUEUE.lock().unwrap().push(mode);
+    Ok(())
+}
+
 #[wasm_bindgen(start)]
 pub fn start() {
-    let mut app = App::new()
+    App::new()
         .add_plugins(
-            (DefaultPlugins
+            DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
                         // fill the entire browser window
@@ -66,21 +546,220 @@ pub fn start() {
                 .set(AssetPlugin {
                     meta_check: AssetMetaCheck::Never,
                     ..default()
-                })),
+                }),
         )
+        .init_asset::<TileCatalog>()
+        .init_asset_loader::<TileCatalogLoader>()
         .add_event::<MouseWheel>()
         .add_event::<HexSelectedEvent>()
+        .add_event::<SetBrushRadiusEvent>()
+        .add_event::<SetPaintToolEvent>()
+        .add_event::<SetFillLimitEvent>()
         .add_event::<ToggleTileLabelsEvent>()
+        .add_event::<SetLabelModeEvent>()
+        .add_event::<ImportMapEvent>()
+        .add_event::<HistoryEvent>()
+        .add_event::<SetAutoCoastEvent>()
+        .add_event::<MapShapeEvent>()
+        .add_event::<ClipboardEvent>()
+        .add_event::<SelectionEvent>()
+        .add_event::<SymmetryEvent>()
+        .add_event::<ScatterEvent>()
+        .add_event::<ChunkChangedEvent>()
         .insert_resource(WorldCoords::default())
         .insert_resource(HoveredTile::default())
-        .insert_resource(SelectedHex(AssetTag::from_str("Erase")))
+        .insert_resource(HexMap::default())
+        .insert_resource(ChunkMeshes::default())
+        .insert_resource(VisibleChunks::default())
+        .insert_resource(EditHistory::default())
+        .insert_resource(Brush::default())
+        .insert_resource(ActivePaintTool::default())
+        .insert_resource(FillSettings::default())
+        .insert_resource(PreviewHexes::default())
+        .insert_resource(Selection::default())
+        .insert_resource(SelectionSettings::default())
+        .insert_resource(Clipboard::default())
+        .insert_resource(Symmetry::default())
+        .insert_resource(ScatterSettings::default())
+        .insert_resource(SelectedHex(AssetTag::Blank))
         .insert_resource(ShowTileLabels(false))
+        .insert_resource(LabelFormat::default())
+        .insert_resource(AutoCoast::default())
         .add_systems(Startup, setup)
         .add_systems(Update, flush_click_events_system)
         .add_systems(Update, on_hex_selected)
+        .add_systems(
+            Update,
+            eyedropper_system
+                .after(cursor_system)
+                .after(on_hex_selected)
+                .before(paint_system),
+        )
+        .add_systems(
+            Update,
+            notify_tile_selected
+                .after(on_hex_selected)
+                .after(eyedropper_system),
+        )
+        .add_systems(Update, (flush_brush_queue, brush_keyboard_system))
+        .add_systems(
+            Update,
+            on_brush_radius_set
+                .after(flush_brush_queue)
+                .after(brush_keyboard_system)
+                .before(paint_system),
+        )
+        .add_systems(Update, (flush_paint_tool_queue, paint_tool_keyboard_system))
+        .add_systems(Update, (flush_clipboard_queue, clipboard_keyboard_system))
+        .add_systems(
+            Update,
+            handle_clipboard_event
+                .after(flush_clipboard_queue)
+                .after(clipboard_keyboard_system)
+                .before(paint_system),
+        )
+        .add_systems(
+            Update,
+            on_paint_tool_set
+                .after(flush_paint_tool_queue)
+                .after(paint_tool_keyboard_system)
+                .after(clipboard_keyboard_system)
+                .after(handle_clipboard_event)
+                .before(paint_system),
+        )
+        .add_systems(Update, (flush_symmetry_queue, symmetry_keyboard_system))
+        .add_systems(
+            Update,
+            handle_symmetry_event
+                .after(flush_symmetry_queue)
+                .after(symmetry_keyboard_system)
+                .before(paint_system),
+        )
         .add_systems(Update, cursor_system)
+        .add_systems(Update, paint_system.after(cursor_system))
+        .add_systems(
+            Update,
+            select_system
+                .after(cursor_system)
+                .after(on_paint_tool_set)
+                .before(preview_system),
+        )
+        .add_systems(Update, flush_scatter_queue)
+        .add_systems(
+            Update,
+            handle_scatter_event
+                .after(flush_scatter_queue)
+                .before(scatter_system),
+        )
+        .add_systems(
+            Update,
+            scatter_system
+                .after(cursor_system)
+                .after(on_paint_tool_set)
+                .after(handle_symmetry_event)
+                .before(paint_system)
+                .before(preview_system)
+                .before(auto_coast_system)
+                .before(sync_tiles_system),
+        )
+        .add_systems(Update, preview_system.after(paint_system))
+        .add_systems(Update, (flush_selection_queue, selection_keyboard_system))
+        .add_systems(
+            Update,
+            handle_selection_event
+                .after(flush_selection_queue)
+                .after(selection_keyboard_system)
+                .after(paint_system)
+                .before(auto_coast_system)
+                .before(sync_tiles_system),
+        )
+        .add_systems(Update, (flush_history_queue, history_keyboard_system))
+        .add_systems(
+            Update,
+            handle_history_event
+                .after(flush_history_queue)
+                .after(history_keyboard_system)
+                .after(paint_system)
+                .before(sync_tiles_system),
+        )
+        .add_systems(Update, flush_map_import_queue)
+        .add_systems(
+            Update,
+            handle_import_map_event
+                .after(flush_map_import_queue)
+                .before(sync_tiles_system),
+        )
+        .add_systems(
+            Update,
+            variant_keyboard_system
+                .after(paint_system)
+                .before(sync_tiles_system),
+        )
+        .add_systems(Update, flush_map_shape_queue)
+        .add_systems(
+            Update,
+            handle_map_shape_event
+                .after(flush_map_shape_queue)
+                .before(sync_tiles_system),
+        )
+        .add_systems(Update, flush_auto_coast_queue)
+        .add_systems(
+            Update,
+            handle_set_auto_coast_event
+                .after(flush_auto_coast_queue)
+                .before(sync_tiles_system),
+        )
+        .add_systems(
+            Update,
+            auto_coast_system
+                .after(paint_system)
+                .after(variant_keyboard_system)
+                .before(sync_tiles_system),
+        )
+        .add_systems(Update, apply_tile_catalog.before(sync_tiles_system))
+        .add_systems(
+            Update,
+            chunk_visibility_system
+                .after(paint_system)
+                .before(sync_tiles_system),
+        )
+        .add_systems(Update, sync_tiles_system.after(paint_system))
+        .add_systems(
+            Update,
+            selection_highlight_system
+                .after(select_system)
+                .after(handle_selection_event)
+                .before(sync_tiles_system),
+        )
+        .add_systems(
+            Update,
+            hover_highlight_system
+                .after(cursor_system)
+                .after(paint_system)
+                .after(select_system)
+                .after(handle_selection_event),
+        )
+        .add_systems(
+            Update,
+            publish_selection_snapshot
+                .after(select_system)
+                .after(handle_selection_event),
+        )
+        .add_systems(Update, publish_map_snapshot.after(sync_tiles_system))
+        .add_systems(Update, autosave_system.after(sync_tiles_system))
         .add_systems(Update, flush_tile_label_toggle_queue)
         .add_systems(Update, handle_toggle_tile_labels_event)
-        .add_systems(Update, toggle_tile_labels_system)
+        .add_systems(Update, flush_label_mode_queue)
+        .add_systems(
+            Update,
+            handle_set_label_mode_event.after(flush_label_mode_queue),
+        )
+        .add_systems(
+            Update,
+            tile_label_system
+                .after(handle_toggle_tile_labels_event)
+                .after(handle_set_label_mode_event)
+                .after(sync_tiles_system),
+        )
         .run();
 }
@@ -0,0 +1,270 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::asset_loading::AssetTag;
+use crate::chunks::ChunkCoord;
+use crate::hex;
+use crate::layout::Orientation;
+use crate::random::mix64;
+
+/// Everything the editor knows about a single hex.
+/// New per-hex fields should be `#[serde(default)]` so older map files keep loading.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexData {
+    pub tag: AssetTag,
+    /// Art variant locked by the user. `None` picks one from the map seed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<u8>,
+    /// Land that auto-coast turned into this coast tile, restored once the hex no longer
+    /// borders water.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub land: Option<AssetTag>,
+}
+
+impl HexData {
+    pub fn new(tag: AssetTag) -> Self {
+        Self {
+            tag,
+            variant: None,
+            land: None,
+        }
+    }
+
+    /// What painting `self` over the hex `current` leaves. Repainting a hex with its own tag
+    /// keeps the hex as it is, locked variant included, unless `self` locks a variant.
+    pub fn painted_over(self, current: Option<&HexData>) -> HexData {
+        match current {
+            Some(current) if current.tag == self.tag && self.variant.is_none() => *current,
+            _ => self,
+        }
+    }
+
+    /// Index of the art variant shown for this hex, out of `count` variants.
+    pub fn variant_index(&self, seed: u64, index: hgs::TileIndex, count: usize) -> usize {
+        if count == 0 {
+            return 0;
+        }
+        match self.variant {
+            Some(variant) => variant as usize % count,
+            None => (mix64(seed ^ mix64(index.0)) % count as u64) as usize,
+        }
+    }
+}
+
+/// The map document: the single source of truth for which hexes exist and what they show.
+///
+/// Chunk meshes are only a view of this resource. Every change marks the touched index as
+/// dirty so that the sync system can rebuild the chunk it belongs to.
+/// Hexes are also indexed by chunk, so that only the chunks in view need meshes.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct HexMap {
+    tiles: HashMap<hgs::TileIndex, HexData>,
+    chunks: HashMap<ChunkCoord, HashSet<hgs::TileIndex>>,
+    dirty: HashSet<hgs::TileIndex>,
+    /// Seed for the per-hex art variant choice.
+    seed: u64,
+    orientation: Orientation,
+    /// Number of hexes in each doubled column and row, so the map's top-left corner is
+    /// known without walking every hex.
+    columns: BTreeMap<i64, usize>,
+    rows: BTreeMap<i64, usize>,
+}
+
+impl HexMap {
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Changes the variant seed, which redraws every hex without a locked variant.
+    pub fn set_seed(&mut self, seed: u64) {
+        if self.seed != seed {
+            self.seed = seed;
+            self.mark_all_dirty();
+        }
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Switches between flat-top and pointy-top, which moves every hex on screen.
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        if self.orientation != orientation {
+            self.orientation = orientation;
+            self.recount_offsets();
+            self.mark_all_dirty();
+        }
+    }
+
+    /// Doubled coordinates of the leftmost column and of the topmost row that contain a
+    /// hex, see `Orientation::offset_coords`.
+    pub fn offset_origin(&self) -> (i64, i64) {
+        let first = |counts: &BTreeMap<i64, usize>| counts.keys().next().copied();
+        (
+            first(&self.columns).unwrap_or_default(),
+            first(&self.rows).unwrap_or_default(),
+        )
+    }
+
+    pub fn get(&self, index: hgs::TileIndex) -> Option<&HexData> {
+        self.tiles.get(&index)
+    }
+
+    pub fn contains(&self, index: hgs::TileIndex) -> bool {
+        self.tiles.contains_key(&index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Iterates over all hexes in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&hgs::TileIndex, &HexData)> {
+        self.tiles.iter()
+    }
+
+    /// Inserts or replaces a hex. Returns the previous data, if any.
+    ///
+    /// Writing identical data is a no-op and does not mark the hex dirty.
+    pub fn set(&mut self, index: hgs::TileIndex, data: HexData) -> Option<HexData> {
+        let previous = self.tiles.insert(index, data);
+        if previous.is_none() {
+            self.count_offsets(index, true);
+            self.chunks
+                .entry(ChunkCoord::of(index))
+                .or_default()
+                .insert(index);
+        }
+        if previous != Some(data) {
+            self.dirty.insert(index);
+        }
+        previous
+    }
+
+    /// Removes a hex from the map. Returns the removed data, if any.
+    pub fn remove(&mut self, index: hgs::TileIndex) -> Option<HexData> {
+        let previous = self.tiles.remove(&index);
+        if previous.is_some() {
+            self.count_offsets(index, false);
+            let coord = ChunkCoord::of(index);
+            if let Some(chunk) = self.chunks.get_mut(&coord) {
+                chunk.remove(&index);
+                if chunk.is_empty() {
+                    self.chunks.remove(&coord);
+                }
+            }
+            self.dirty.insert(index);
+        }
+        previous
+    }
+
+    /// Replaces the whole map, marking both the old and the new hexes dirty.
+    pub fn replace_all(&mut self, tiles: impl IntoIterator<Item = (hgs::TileIndex, HexData)>) {
+        self.dirty.extend(self.tiles.keys().copied());
+        self.tiles = tiles.into_iter().collect();
+        self.chunks.clear();
+        for &index in self.tiles.keys() {
+            self.chunks
+                .entry(ChunkCoord::of(index))
+                .or_default()
+                .insert(index);
+        }
+        self.recount_offsets();
+        self.dirty.extend(self.tiles.keys().copied());
+    }
+
+    /// Chunks that contain at least one hex.
+    pub fn chunk_coords(&self) -> impl Iterator<Item = ChunkCoord> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    /// The hexes of one chunk.
+    pub fn chunk_tiles(&self, coord: ChunkCoord) -> impl Iterator<Item = hgs::TileIndex> + '_ {
+        self.chunks.get(&coord).into_iter().flatten().copied()
+    }
+
+    /// Marks hexes dirty without changing them, so the sync system revisits them.
+    pub fn mark_dirty(&mut self, indices: impl IntoIterator<Item = hgs::TileIndex>) {
+        self.dirty.extend(indices);
+    }
+
+    /// Marks every hex dirty, e.g. after the tile images changed.
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty.extend(self.tiles.keys().copied());
+    }
+
+    /// Returns and clears the set of hexes changed since the last call.
+    pub fn take_dirty(&mut self) -> HashSet<hgs::TileIndex> {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Counts a hex into (or, when `added` is false, out of) its doubled column and row.
+    fn count_offsets(&mut self, index: hgs::TileIndex, added: bool) {
+        let (column, row) = self.orientation.doubled_coords(hex::cube(index));
+        for (counts, key) in [(&mut self.columns, column), (&mut self.rows, row)] {
+            let count = counts.entry(key).or_default();
+            if added {
+                *count += 1;
+            } else {
+                *count -= 1;
+                if *count == 0 {
+                    counts.remove(&key);
+                }
+            }
+        }
+    }
+
+    fn recount_offsets(&mut self) {
+        self.columns.clear();
+        self.rows.clear();
+        let indices: Vec<hgs::TileIndex> = self.tiles.keys().copied().collect();
+        for index in indices {
+            self.count_offsets(index, true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::HexDirection;
+
+    /// The offset origin found by walking every hex.
+    fn scanned_origin(map: &HexMap) -> (i64, i64) {
+        let orientation = map.orientation();
+        map.iter()
+            .map(|(index, _)| orientation.doubled_coords(hex::cube(*index)))
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1)))
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn offset_origin_follows_the_hexes() {
+        let mut map = HexMap::default();
+        let data = HexData::new(AssetTag::Blank);
+        let cubes = [
+            (0, 0, 0),
+            HexDirection::East.offset(),
+            hex::scale(HexDirection::NorthEast.offset(), 3),
+            hex::scale(HexDirection::West.offset(), 2),
+        ];
+        for cube in cubes {
+            map.set(hex::index_of(cube), data);
+            assert_eq!(map.offset_origin(), scanned_origin(&map));
+        }
+        map.set_orientation(Orientation::Pointy);
+        assert_eq!(map.offset_origin(), scanned_origin(&map));
+        for cube in cubes.into_iter().rev().skip(1) {
+            map.remove(hex::index_of(cube));
+            assert_eq!(map.offset_origin(), scanned_origin(&map));
+        }
+        map.replace_all([(hex::index_of(cubes[3]), data)]);
+        assert_eq!(map.offset_origin(), scanned_origin(&map));
+    }
+}
@@ -0,0 +1,130 @@
+use hexgridspiral as hgs;
+use serde::{Deserialize, Serialize};
+
+use crate::asset_loading::AssetTag;
+use crate::layout::Orientation;
+use crate::map::{HexData, HexMap};
+
+/// Version of the JSON map format written by `export_map`.
+/// Bump this whenever a change to the schema cannot be read by older builds.
+pub const MAP_FORMAT_VERSION: u32 = 1;
+
+/// Serializable snapshot of a `HexMap`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MapDocument {
+    pub version: u32,
+    /// Seed for the per-hex art variant choice.
+    #[serde(default)]
+    pub seed: u64,
+    /// Flat-top or pointy-top; maps saved before the setting existed are flat-top.
+    #[serde(default)]
+    pub orientation: Orientation,
+    pub tiles: Vec<TileRecord>,
+}
+
+/// A single hex in a `MapDocument`, keyed by its spiral index.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct TileRecord {
+    pub index: u64,
+    #[serde(flatten)]
+    pub data: HexData,
+}
+
+impl Default for MapDocument {
+    fn default() -> Self {
+        Self {
+            version: MAP_FORMAT_VERSION,
+            seed: 0,
+            orientation: Orientation::default(),
+            tiles: Vec::new(),
+        }
+    }
+}
+
+impl MapDocument {
+    /// Captures the current map, ordered by spiral index so exports are stable.
+    pub fn from_map(map: &HexMap) -> Self {
+        let mut tiles: Vec<TileRecord> = map
+            .iter()
+            .map(|(index, data)| TileRecord {
+                index: index.0,
+                data: *data,
+            })
+            .collect();
+        tiles.sort_by_key(|record| record.index);
+
+        Self {
+            version: MAP_FORMAT_VERSION,
+            seed: map.seed(),
+            orientation: map.orientation(),
+            tiles,
+        }
+    }
+
+    /// The hexes of the document. Older builds "erased" hexes by painting them with the
+    /// invisible `AssetTag::None`; such records are dropped, as the hexes are really gone.
+    pub fn tiles(&self) -> impl Iterator<Item = (hgs::TileIndex, HexData)> + '_ {
+        self.tiles
+            .iter()
+            .filter(|record| record.data.tag != AssetTag::None)
+            .map(|record| (hgs::TileIndex::from(record.index), record.data))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("map document is always serializable")
+    }
+
+    /// Parses a document, rejecting files written by a newer format version.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let document: MapDocument =
+            serde_json::from_str(json).map_err(|err| format!("Invalid map file: {}", err))?;
+        if document.version > MAP_FORMAT_VERSION {
+            return Err(format!(
+                "Map format version {} is newer than the supported version {}",
+                document.version, MAP_FORMAT_VERSION
+            ));
+        }
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_round_trips() {
+        let mut map = HexMap::default();
+        map.set_seed(7);
+        map.set_orientation(Orientation::Pointy);
+        map.set(hgs::TileIndex::from(9), HexData::new(AssetTag::HillsLush));
+        map.set(hgs::TileIndex::from(2), HexData::new(AssetTag::PlainsLush));
+
+        let document = MapDocument::from_json(&MapDocument::from_map(&map).to_json()).unwrap();
+        assert_eq!(document.version, MAP_FORMAT_VERSION);
+        assert_eq!(document.seed, 7);
+        assert_eq!(document.orientation, Orientation::Pointy);
+        let indices: Vec<u64> = document.tiles.iter().map(|record| record.index).collect();
+        assert_eq!(indices, [2, 9]);
+    }
+
+    #[test]
+    fn old_files_load_with_defaults() {
+        let json =
+            r#"{"version":1,"tiles":[{"index":3,"tag":"PlainsLush"},{"index":4,"tag":"None"}]}"#;
+        let document = MapDocument::from_json(json).unwrap();
+        assert_eq!(document.seed, 0);
+        assert_eq!(document.orientation, Orientation::Flat);
+        let tiles: Vec<_> = document
+            .tiles()
+            .map(|(index, data)| (index.0, data))
+            .collect();
+        assert_eq!(tiles, [(3, HexData::new(AssetTag::PlainsLush))]);
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let json = format!(r#"{{"version":{},"tiles":[]}}"#, MAP_FORMAT_VERSION + 1);
+        assert!(MapDocument::from_json(&json).is_err());
+    }
+}
@@ -0,0 +1,284 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use wasm_bindgen::JsValue;
+
+use crate::asset_loading::AssetTag;
+use crate::layout::Orientation;
+use crate::map::HexData;
+use crate::map_document::{MapDocument, TileRecord};
+use crate::map_shape::MAX_MAP_HEXES;
+
+/// Version byte at the start of every encoded link.
+const LINK_FORMAT_VERSION: u8 = 1;
+/// Code for a spiral index without a hex. Other codes are positions in the link's tile id
+/// table, counting from 1.
+const EMPTY_CODE: u64 = 0;
+/// Prefix of the URL fragment that carries a map.
+const FRAGMENT_PREFIX: &str = "#map=";
+
+/// Encodes the tags of a map into a compact, URL-safe string.
+///
+/// After the version byte, the variant seed and the orientation byte comes a table of the
+/// stable tile ids the map uses, each a varint length followed by the id. Then hexes are
+/// visited in spiral-index order from 0 up to the highest used index, and each run of
+/// identical codes is written as a `(run length, code)` pair of LEB128 varints.
+/// Only tags are stored; use `export_map` to keep everything else.
+pub fn encode(document: &MapDocument) -> String {
+    let mut bytes = vec![LINK_FORMAT_VERSION];
+    write_varint(&mut bytes, document.seed);
+    bytes.push(document.orientation as u8);
+
+    let mut ids: Vec<AssetTag> = Vec::new();
+    let mut runs = Vec::new();
+    let mut next_index = 0;
+    for record in &document.tiles {
+        let tag = record.data.tag;
+        let position = match ids.iter().position(|id| *id == tag) {
+            Some(position) => position,
+            None => {
+                ids.push(tag);
+                ids.len() - 1
+            }
+        };
+        // The gap before a stored hex is a single run of empty codes.
+        extend_run(
+            &mut runs,
+            record.index.saturating_sub(next_index),
+            EMPTY_CODE,
+        );
+        extend_run(&mut runs, 1, position as u64 + 1);
+        next_index = record.index + 1;
+    }
+
+    write_varint(&mut bytes, ids.len() as u64);
+    for id in ids {
+        let id: &'static str = id.into();
+        write_varint(&mut bytes, id.len() as u64);
+        bytes.extend_from_slice(id.as_bytes());
+    }
+    for (length, code) in runs {
+        write_varint(&mut bytes, length);
+        write_varint(&mut bytes, code);
+    }
+
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Appends `length` hexes of `code` to `runs`, lengthening the last run if it has the
+/// same code.
+fn extend_run(runs: &mut Vec<(u64, u64)>, length: u64, code: u64) {
+    match runs.last_mut() {
+        _ if length == 0 => {}
+        Some((last_length, last_code)) if *last_code == code => *last_length += length,
+        _ => runs.push((length, code)),
+    }
+}
+
+/// Decodes a string produced by `encode`. Links with more than `MAX_MAP_HEXES` hexes are
+/// rejected.
+pub fn decode(payload: &str) -> Result<MapDocument, String> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(payload.trim())
+        .map_err(|err| format!("Invalid map link: {}", err))?;
+    let (&version, mut rest) = bytes.split_first().ok_or("Empty map link")?;
+    if version != LINK_FORMAT_VERSION {
+        return Err(format!("Unsupported map link version {}", version));
+    }
+
+    let mut document = MapDocument {
+        seed: read_varint(&mut rest)?,
+        ..MapDocument::default()
+    };
+    let (&orientation, tail) = rest.split_first().ok_or("Truncated map link")?;
+    document.orientation = Orientation::from_repr(orientation)
+        .ok_or_else(|| format!("Unknown orientation {} in map link", orientation))?;
+    rest = tail;
+
+    let id_count = read_varint(&mut rest)?;
+    let mut ids = Vec::new();
+    for _ in 0..id_count {
+        let length = usize::try_from(read_varint(&mut rest)?).map_err(|_| "Malformed map link")?;
+        if length > rest.len() {
+            return Err("Truncated map link".to_string());
+        }
+        let (id, tail) = rest.split_at(length);
+        rest = tail;
+        let id = std::str::from_utf8(id).map_err(|_| "Malformed map link")?;
+        let tag: AssetTag = id
+            .parse()
+            .map_err(|_| format!("Unknown tile id {} in map link", id))?;
+        ids.push(tag);
+    }
+
+    let mut index = 0u64;
+    while !rest.is_empty() {
+        let length = read_varint(&mut rest)?;
+        let code = read_varint(&mut rest)?;
+        let end = index.checked_add(length).ok_or("Malformed map link")?;
+        if code != EMPTY_CODE {
+            if (document.tiles.len() as u64).saturating_add(length) > MAX_MAP_HEXES as u64 {
+                return Err(format!(
+                    "Map link has more than the limit of {} hexes",
+                    MAX_MAP_HEXES
+                ));
+            }
+            let tag = usize::try_from(code - 1)
+                .ok()
+                .and_then(|position| ids.get(position).copied())
+                .ok_or("Malformed map link")?;
+            document.tiles.extend((index..end).map(|index| TileRecord {
+                index,
+                data: HexData::new(tag),
+            }));
+        }
+        index = end;
+    }
+    Ok(document)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, String> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first().ok_or("Truncated map link")?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("Malformed map link".to_string())
+}
+
+/// Reads a map from the page URL fragment (`#map=...`), if there is one.
+pub fn read_from_location() -> Option<Result<MapDocument, String>> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    parse_fragment(&hash)
+}
+
+/// Decodes a `#map=...` fragment. Returns `None` if the fragment carries no map.
+pub fn parse_fragment(fragment: &str) -> Option<Result<MapDocument, String>> {
+    fragment.strip_prefix(FRAGMENT_PREFIX).map(decode)
+}
+
+/// Writes the encoded map into the URL fragment without adding a history entry,
+/// and returns the resulting shareable link.
+pub fn write_to_location(document: &MapDocument) -> Result<String, JsValue> {
+    let window = web_sys::window().ok_or("No browser window")?;
+    let fragment = format!("{}{}", FRAGMENT_PREFIX, encode(document));
+    window
+        .history()?
+        .replace_state_with_url(&JsValue::NULL, "", Some(&fragment))?;
+    window.location().href()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(index: u64, tag: AssetTag) -> TileRecord {
+        TileRecord {
+            index,
+            data: HexData::new(tag),
+        }
+    }
+
+    /// A link whose tile id table holds only `PlainsLush`, so code 1 is `PlainsLush`.
+    fn link(runs: &[(u64, u64)]) -> String {
+        let mut bytes = vec![LINK_FORMAT_VERSION];
+        write_varint(&mut bytes, 0);
+        bytes.push(Orientation::default() as u8);
+        let id: &'static str = AssetTag::PlainsLush.into();
+        write_varint(&mut bytes, 1);
+        write_varint(&mut bytes, id.len() as u64);
+        bytes.extend_from_slice(id.as_bytes());
+        for &(length, code) in runs {
+            write_varint(&mut bytes, length);
+            write_varint(&mut bytes, code);
+        }
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    #[test]
+    fn links_round_trip() {
+        let document = MapDocument {
+            seed: 1234,
+            orientation: Orientation::Pointy,
+            tiles: vec![
+                record(0, AssetTag::PlainsLush),
+                record(1, AssetTag::PlainsLush),
+                record(5, AssetTag::OceanStill),
+                record(9, AssetTag::PlainsLush),
+            ],
+            ..MapDocument::default()
+        };
+        let decoded = decode(&encode(&document)).unwrap();
+        assert_eq!(decoded.seed, document.seed);
+        assert_eq!(decoded.orientation, document.orientation);
+        let tiles = |document: &MapDocument| -> Vec<(u64, HexData)> {
+            document
+                .tiles
+                .iter()
+                .map(|record| (record.index, record.data))
+                .collect()
+        };
+        assert_eq!(tiles(&decoded), tiles(&document));
+    }
+
+    #[test]
+    fn gaps_are_one_run() {
+        let document = MapDocument {
+            tiles: vec![record(1 << 40, AssetTag::PlainsLush)],
+            ..MapDocument::default()
+        };
+        let encoded = encode(&document);
+        let adjacent = encode(&MapDocument {
+            tiles: vec![record(0, AssetTag::PlainsLush)],
+            ..MapDocument::default()
+        });
+        assert!(encoded.len() < adjacent.len() + 12, "{}", encoded);
+        let decoded = decode(&encoded).unwrap();
+        assert_eq!(decoded.tiles.len(), 1);
+        assert_eq!(decoded.tiles[0].index, 1 << 40);
+    }
+
+    #[test]
+    fn tiles_are_stored_by_id() {
+        let decoded = decode(&link(&[(2, EMPTY_CODE), (1, 1)])).unwrap();
+        assert_eq!(decoded.tiles.len(), 1);
+        assert_eq!(decoded.tiles[0].index, 2);
+        assert_eq!(decoded.tiles[0].data.tag, AssetTag::PlainsLush);
+        assert!(decode(&link(&[(1, 2)])).is_err());
+    }
+
+    #[test]
+    fn overflowing_indices_are_rejected() {
+        assert!(decode(&link(&[(u64::MAX, EMPTY_CODE), (2, 1)])).is_err());
+    }
+
+    #[test]
+    fn oversized_maps_are_rejected() {
+        let code = 1;
+        assert!(decode(&link(&[(MAX_MAP_HEXES as u64, code)])).is_ok());
+        assert!(decode(&link(&[(MAX_MAP_HEXES as u64 + 1, code)])).is_err());
+        assert!(
+            decode(&link(&[
+                (MAX_MAP_HEXES as u64, code),
+                (1, EMPTY_CODE),
+                (1, code)
+            ]))
+            .is_err()
+        );
+        assert!(decode(&link(&[(u64::MAX, code)])).is_err());
+    }
+}
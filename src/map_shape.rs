@@ -0,0 +1,169 @@
+use hexgridspiral as hgs;
+use serde::{Deserialize, Serialize};
+
+use crate::hex::{self, Cube, HexDirection};
+use crate::layout::Orientation;
+
+/// Most hexes a map may have. Larger shapes are rejected instead of allocated, and shared
+/// map links are held to the same limit.
+pub const MAX_MAP_HEXES: usize = 250_000;
+
+/// Outline of a map, as chosen in the "new map" dialog.
+///
+/// Sizes count hexes as seen on screen, `width` across and `height` down, in either
+/// orientation. All shapes are centred on the origin hex.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "shape", rename_all = "lowercase")]
+pub enum MapShape {
+    /// Every hex within `radius` steps of the origin.
+    Hexagon { radius: u32 },
+    /// Straight rows with every other column shifted by half a hex.
+    Rectangle { width: u32, height: u32 },
+    /// Rows that lean by half a hex per column.
+    Parallelogram { width: u32, height: u32 },
+}
+
+impl Default for MapShape {
+    /// The 61 hexes the editor has always started with.
+    fn default() -> Self {
+        MapShape::Hexagon { radius: 4 }
+    }
+}
+
+impl MapShape {
+    /// Number of hexes in the shape.
+    pub fn hex_count(&self) -> u64 {
+        match *self {
+            MapShape::Hexagon { radius } => {
+                let radius = radius as u64;
+                radius
+                    .saturating_mul(radius + 1)
+                    .saturating_mul(3)
+                    .saturating_add(1)
+            }
+            MapShape::Rectangle { width, height } | MapShape::Parallelogram { width, height } => {
+                width as u64 * height as u64
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let count = self.hex_count();
+        if count == 0 {
+            return Err(format!("Map size must not be empty: {:?}", self));
+        }
+        if count > MAX_MAP_HEXES as u64 {
+            return Err(format!(
+                "Map of {} hexes is larger than the limit of {} hexes",
+                count, MAX_MAP_HEXES
+            ));
+        }
+        Ok(())
+    }
+
+    /// Spiral indices of every hex in the shape, laid out for the given orientation.
+    pub fn indices(&self, orientation: Orientation) -> Vec<hgs::TileIndex> {
+        self.cubes(orientation)
+            .into_iter()
+            .map(hex::index_of)
+            .collect()
+    }
+
+    fn cubes(&self, orientation: Orientation) -> Vec<Cube> {
+        // The layouts below stack rows of the art frame; on a flat-top map those rows
+        // are screen columns.
+        let shape = match (*self, orientation) {
+            (MapShape::Rectangle { width, height }, Orientation::Pointy) => MapShape::Rectangle {
+                width: height,
+                height: width,
+            },
+            (MapShape::Parallelogram { width, height }, Orientation::Pointy) => {
+                MapShape::Parallelogram {
+                    width: height,
+                    height: width,
+                }
+            }
+            (shape, _) => shape,
+        };
+        match shape {
+            MapShape::Hexagon { radius } => hex::range((0, 0, 0), radius as i64),
+            MapShape::Rectangle { width, height } => centred(width, height, |column, row| {
+                // Two columns up and back down again cancel out sideways.
+                add_all([
+                    hex::scale(HexDirection::East.offset(), row),
+                    hex::scale(HexDirection::NorthEast.offset(), (column + 1) / 2),
+                    hex::scale(HexDirection::NorthWest.offset(), column / 2),
+                ])
+            }),
+            MapShape::Parallelogram { width, height } => centred(width, height, |column, row| {
+                add_all([
+                    hex::scale(HexDirection::East.offset(), row),
+                    hex::scale(HexDirection::NorthEast.offset(), column),
+                ])
+            }),
+        }
+    }
+}
+
+/// Lays out `width` rows of `height` hexes along the east axis of the tile art, and moves
+/// the middle one onto the origin.
+fn centred(width: u32, height: u32, layout: impl Fn(i64, i64) -> Cube) -> Vec<Cube> {
+    let (width, height) = (width as i64, height as i64);
+    let middle = layout(width / 2, height / 2);
+    let mut cubes = Vec::with_capacity((width * height) as usize);
+    for column in 0..width {
+        for row in 0..height {
+            cubes.push(hex::subtract(layout(column, row), middle));
+        }
+    }
+    cubes
+}
+
+fn add_all(cubes: impl IntoIterator<Item = Cube>) -> Cube {
+    cubes.into_iter().fold((0, 0, 0), hex::add)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn hex_count_matches_the_layout() {
+        let shapes = [
+            MapShape::default(),
+            MapShape::Rectangle {
+                width: 5,
+                height: 3,
+            },
+            MapShape::Parallelogram {
+                width: 2,
+                height: 7,
+            },
+        ];
+        for shape in shapes {
+            for orientation in [Orientation::Flat, Orientation::Pointy] {
+                let indices: HashSet<_> = shape.indices(orientation).into_iter().collect();
+                assert_eq!(indices.len() as u64, shape.hex_count(), "{:?}", shape);
+            }
+        }
+        assert_eq!(MapShape::default().hex_count(), 61);
+    }
+
+    #[test]
+    fn validate_rejects_empty_and_huge_maps() {
+        assert!(MapShape::default().validate().is_ok());
+        let empty = MapShape::Rectangle {
+            width: 0,
+            height: 4,
+        };
+        assert!(empty.validate().is_err());
+        let huge = MapShape::Hexagon { radius: u32::MAX };
+        assert!(huge.validate().is_err());
+        let wide = MapShape::Parallelogram {
+            width: u32::MAX,
+            height: u32::MAX,
+        };
+        assert!(wide.validate().is_err());
+    }
+}
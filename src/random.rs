@@ -0,0 +1,35 @@
+/// SplitMix64 finalizer: a cheap, well-distributed hash for seeds and indices.
+/// Unlike `std`'s hashers its output is stable across builds and platforms.
+pub fn mix64(value: u64) -> u64 {
+    let mut x = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// A fresh random seed from the browser's crypto RNG.
+pub fn random_seed() -> u64 {
+    let mut bytes = [0u8; 8];
+    if let Err(err) = getrandom::fill(&mut bytes) {
+        log::warn!("Could not get a random seed: {}", err);
+    }
+    u64::from_le_bytes(bytes)
+}
+
+/// Small seeded generator (SplitMix64): the same seed always gives the same sequence.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let value = mix64(self.state);
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        value
+    }
+}
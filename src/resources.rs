@@ -1,29 +1,237 @@
-use crate::asset_loading::AssetTag;
+use crate::asset_loading::{AssetTag, TileFacing};
+use crate::chunks::ChunkCoord;
+use crate::map::HexData;
+use crate::random::mix64;
 use bevy::prelude::*;
 use hexgridspiral as hgs;
 use std::collections::{HashMap, HashSet};
+use strum_macros::{EnumString, IntoStaticStr};
 
-pub const NUM_TILES: u64 = 61;
-
-/// Stores handles to tile image assets.
+/// Stores handles to tile image assets: every art variant of each tag,
+/// plus how the art of each tag is turned when drawn.
 #[derive(Resource)]
 pub struct TileImageHandles {
-    pub handles: HashMap<AssetTag, Handle<Image>>,
+    pub handles: HashMap<AssetTag, Vec<Handle<Image>>>,
+    pub facings: HashMap<AssetTag, TileFacing>,
 }
 
-/// Tracks the user’s selected tool.
+impl TileImageHandles {
+    pub fn facing(&self, tag: AssetTag) -> TileFacing {
+        self.facings.get(&tag).copied().unwrap_or_default()
+    }
+
+    pub fn variant_count(&self, tag: AssetTag) -> usize {
+        self.handles.get(&tag).map_or(0, Vec::len)
+    }
+
+    /// Returns the image for a hex, falling back to the transparent `AssetTag::None` image.
+    pub fn for_hex(&self, seed: u64, index: hgs::TileIndex, data: &HexData) -> Handle<Image> {
+        match self
+            .handles
+            .get(&data.tag)
+            .filter(|variants| !variants.is_empty())
+        {
+            Some(variants) => variants[data.variant_index(seed, index, variants.len())].clone(),
+            None => self.transparent(),
+        }
+    }
+
+    pub fn transparent(&self) -> Handle<Image> {
+        self.handles
+            .get(&AssetTag::None)
+            .and_then(|variants| variants.first())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// What a left click on the map does with the selected tile.
+#[derive(EnumString, IntoStaticStr, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum PaintTool {
+    /// Paint every hex under the brush while the button is held.
+    #[default]
+    Brush,
+    /// Repaint the contiguous region of same-tile hexes that was clicked.
+    Fill,
+    /// Paint the hex line from where the button was pressed to where it was released.
+    Line,
+    /// Paint a hollow ring around the pressed hex, reaching out to the released hex.
+    Ring,
+    /// Paint a filled hexagon around the pressed hex, reaching out to the released hex.
+    Hexagon,
+    /// Select hexes instead of painting them, in the active `SelectShape`.
+    Select,
+    /// Put the clipboard down with its centre on the clicked hex.
+    Paste,
+    /// Paint random tiles from the `ScatterSettings` palette under the brush.
+    Scatter,
+}
+
+#[derive(Resource, Default)]
+pub struct ActivePaintTool(pub PaintTool);
+
+/// Settings of the fill tool.
+#[derive(Resource, Default)]
+pub struct FillSettings {
+    /// Furthest a fill reaches from the clicked hex, in steps. `None` is unbounded.
+    pub max_distance: Option<u32>,
+}
+
+/// Settings of the scatter brush: a weighted palette and how densely it is scattered.
 #[derive(Resource)]
+pub struct ScatterSettings {
+    /// Tiles with relative weights: with weights 60, 30 and 10, the first tile lands on
+    /// 60% of the painted hexes.
+    pub palette: Vec<(AssetTag, u32)>,
+    /// Percentage of hexes under the brush that get painted; the rest are left untouched.
+    pub density: u8,
+}
+
+impl Default for ScatterSettings {
+    fn default() -> Self {
+        Self {
+            palette: vec![
+                (AssetTag::ForestConiferLush, 60),
+                (AssetTag::SparseTreesLush, 30),
+                (AssetTag::HillsLush, 10),
+            ],
+            density: 50,
+        }
+    }
+}
+
+impl ScatterSettings {
+    /// What a stroke scattered with `seed` paints at `index`, or `None` to leave the hex
+    /// untouched. The same seed and index always give the same answer, so a hex swept over
+    /// again in the same stroke keeps its tile.
+    pub fn pick(&self, seed: u64, index: hgs::TileIndex) -> Option<AssetTag> {
+        let total: u64 = self.palette.iter().map(|(_, weight)| *weight as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        let roll = mix64(seed ^ mix64(index.0));
+        if roll % 100 >= self.density as u64 {
+            return None;
+        }
+        let mut choice = mix64(roll) % total;
+        for &(tag, weight) in &self.palette {
+            if choice < weight as u64 {
+                return Some(tag);
+            }
+            choice -= weight as u64;
+        }
+        None
+    }
+}
+
+/// Largest brush radius, in steps from the hovered hex.
+pub const MAX_BRUSH_RADIUS: u8 = 5;
+
+/// Brush settings for painting. A radius of 0 paints just the hovered hex.
+#[derive(Resource, Default)]
+pub struct Brush {
+    pub radius: u8,
+}
+
+/// Hexes shown as a translucent preview of what the current tool would paint.
+/// Hexes in the preview don't need to exist in the map.
+#[derive(Resource, Default, PartialEq)]
+pub struct PreviewHexes(pub HashMap<hgs::TileIndex, AssetTag>);
+
+/// How the select tool picks hexes.
+#[derive(EnumString, IntoStaticStr, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum SelectShape {
+    /// Select the hexes under the brush while dragging.
+    #[default]
+    Brush,
+    /// Select the hexes whose centres lie in the rectangle dragged out.
+    Rectangle,
+    /// Select the hexes whose centres lie inside the outline drawn while dragging.
+    Lasso,
+}
+
+/// Settings of the select tool.
+#[derive(Resource, Default)]
+pub struct SelectionSettings {
+    pub shape: SelectShape,
+}
+
+/// Hexes picked with the select tool, e.g. for copying. They don't need to exist in the map.
+#[derive(Resource, Default)]
+pub struct Selection(pub HashSet<hgs::TileIndex>);
+
+/// Tracks the user’s selected tool.
+#[derive(Resource, PartialEq)]
 pub struct SelectedHex(pub AssetTag);
 
 /// Stores current world cursor position.
 #[derive(Resource, Default)]
 pub struct WorldCoords(pub(crate) Vec2);
 
-/// Tracks the currently hovered tile entity.
+/// The entity drawing each chunk in view, kept up to date by the sync system. Its children
+/// hold one mesh per tile image.
+#[derive(Resource, Default)]
+pub struct ChunkMeshes(pub HashMap<ChunkCoord, Entity>);
+
+/// Tracks the hex currently under the cursor (whether or not it exists in the map).
 #[derive(Resource, Default)]
 pub struct HoveredTile {
-    entity: Option<hgs::CCTile>,
+    pub index: Option<hgs::TileIndex>,
 }
 
 #[derive(Resource, Default)]
 pub struct ShowTileLabels(pub bool);
+
+/// Whether beach tiles are placed automatically along the water's edge.
+#[derive(Resource, Default)]
+pub struct AutoCoast(pub bool);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    fn settings(palette: &[(AssetTag, u32)], density: u8) -> ScatterSettings {
+        ScatterSettings {
+            palette: palette.to_vec(),
+            density,
+        }
+    }
+
+    fn indices() -> impl Iterator<Item = hgs::TileIndex> {
+        hex::within(hex::index_of((0, 0, 0)), 12)
+    }
+
+    #[test]
+    fn density_decides_how_many_hexes_are_painted() {
+        let palette = [(AssetTag::HillsLush, 1)];
+        assert!(indices().all(|index| settings(&palette, 0).pick(7, index).is_none()));
+        assert!(indices().all(|index| settings(&palette, 100).pick(7, index).is_some()));
+
+        let half = settings(&palette, 50);
+        let painted = indices()
+            .filter(|index| half.pick(7, *index).is_some())
+            .count();
+        let total = indices().count();
+        assert!(
+            painted > total / 3 && painted < total * 2 / 3,
+            "{painted} of {total}"
+        );
+    }
+
+    #[test]
+    fn picks_follow_the_weights() {
+        let only_hills = settings(&[(AssetTag::PlainsLush, 0), (AssetTag::HillsLush, 5)], 100);
+        assert!(indices().all(|index| only_hills.pick(3, index) == Some(AssetTag::HillsLush)));
+        assert!(indices().all(|index| settings(&[], 100).pick(3, index).is_none()));
+    }
+
+    #[test]
+    fn picks_repeat_for_the_same_seed() {
+        let scatter = ScatterSettings::default();
+        assert!(indices().all(|index| scatter.pick(11, index) == scatter.pick(11, index)));
+        assert!(indices().any(|index| scatter.pick(11, index) != scatter.pick(12, index)));
+    }
+}
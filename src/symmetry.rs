@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+
+use crate::asset_loading::AssetTag;
+use crate::hex::{self, Cube};
+use crate::layout::Orientation;
+
+/// Numbers of rotated copies that land exactly on hexes.
+pub const ROTATION_COUNTS: [u8; 4] = [1, 2, 3, 6];
+
+/// Symmetric painting: every edit is repeated at its mirror images and its copies turned
+/// around `centre`.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symmetry {
+    /// Mirror across the horizontal line through the centre, so top and bottom match.
+    pub mirror_horizontal: bool,
+    /// Mirror across the vertical line through the centre, so left and right match.
+    pub mirror_vertical: bool,
+    /// Copies spread evenly around the centre, counting the original: one of
+    /// `ROTATION_COUNTS`.
+    pub rotations: u8,
+    pub centre: hgs::TileIndex,
+}
+
+impl Default for Symmetry {
+    fn default() -> Self {
+        Self {
+            mirror_horizontal: false,
+            mirror_vertical: false,
+            rotations: 1,
+            centre: hex::index_of((0, 0, 0)),
+        }
+    }
+}
+
+/// How a symmetric counterpart is turned relative to the hex it repeats: mirrored across
+/// the east-west axis (if `mirrored`), then turned counterclockwise by `rotation` times 60°.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Turn {
+    pub rotation: u8,
+    pub mirrored: bool,
+}
+
+impl Turn {
+    /// Where a cube offset from the symmetry centre ends up.
+    pub fn cube(self, offset: Cube) -> Cube {
+        let offset = if self.mirrored {
+            hex::mirror(offset)
+        } else {
+            offset
+        };
+        hex::rotate(offset, self.rotation)
+    }
+
+    /// What a tile turns into, so that coasts keep facing the same way relative to the
+    /// centre; see `AssetTag::turned`.
+    pub fn tag(self, tag: AssetTag) -> AssetTag {
+        tag.turned(self.rotation, self.mirrored)
+    }
+}
+
+impl Symmetry {
+    pub fn is_active(&self) -> bool {
+        self.mirror_horizontal || self.mirror_vertical || self.rotations > 1
+    }
+
+    /// `index` followed by every hex symmetric to it, without duplicates, each with the
+    /// turn that takes `index` there.
+    pub fn counterparts(
+        &self,
+        index: hgs::TileIndex,
+        orientation: Orientation,
+    ) -> Vec<(hgs::TileIndex, Turn)> {
+        if !self.is_active() {
+            return vec![(index, Turn::default())];
+        }
+        let centre = hex::cube(self.centre);
+        let offset = hex::subtract(hex::cube(index), centre);
+
+        // `hex::mirror` swaps north and south of the tile art. Flat-top maps draw the art
+        // turned by 90°, so there it mirrors left and right instead. Mirroring across the
+        // other axis is the same plus a half turn.
+        let art_axis = Turn {
+            rotation: 0,
+            mirrored: true,
+        };
+        let other_axis = Turn {
+            rotation: 3,
+            mirrored: true,
+        };
+        let (horizontal, vertical) = match orientation {
+            Orientation::Pointy => (art_axis, other_axis),
+            Orientation::Flat => (other_axis, art_axis),
+        };
+        let mut images = vec![Turn::default()];
+        if self.mirror_horizontal {
+            images.push(horizontal);
+        }
+        if self.mirror_vertical {
+            images.push(vertical);
+        }
+        if self.mirror_horizontal && self.mirror_vertical {
+            // Mirroring across both axes is a half turn.
+            images.push(Turn {
+                rotation: 3,
+                mirrored: false,
+            });
+        }
+
+        let rotations = self.rotations.max(1);
+        let mut counterparts: Vec<(hgs::TileIndex, Turn)> = Vec::new();
+        for image in images {
+            for copy in 0..rotations {
+                let turn = Turn {
+                    rotation: (image.rotation + copy * 6 / rotations) % 6,
+                    ..image
+                };
+                let counterpart = hex::index_of(hex::add(centre, turn.cube(offset)));
+                if !counterparts.iter().any(|(index, _)| *index == counterpart) {
+                    counterparts.push((counterpart, turn));
+                }
+            }
+        }
+        counterparts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_loading::CoastSize;
+    use crate::hex::HexDirection;
+
+    fn symmetry(mirror_horizontal: bool, mirror_vertical: bool, rotations: u8) -> Symmetry {
+        Symmetry {
+            mirror_horizontal,
+            mirror_vertical,
+            rotations,
+            ..Symmetry::default()
+        }
+    }
+
+    #[test]
+    fn inactive_symmetry_keeps_the_hex() {
+        let index = hex::index_of((2, -1, -1));
+        let counterparts = Symmetry::default().counterparts(index, Orientation::Flat);
+        assert!(counterparts == [(index, Turn::default())]);
+    }
+
+    #[test]
+    fn counterparts_land_where_their_turn_takes_them() {
+        let offset = (3, -1, -2);
+        for orientation in [Orientation::Flat, Orientation::Pointy] {
+            for rotations in ROTATION_COUNTS {
+                let counterparts = symmetry(true, true, rotations)
+                    .counterparts(hex::index_of(offset), orientation);
+                assert!(counterparts[0] == (hex::index_of(offset), Turn::default()));
+                for (index, turn) in counterparts {
+                    assert_eq!(hex::cube(index), turn.cube(offset));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rotated_coasts_keep_facing_outwards() {
+        let east = HexDirection::East;
+        let counterparts =
+            symmetry(false, false, 6).counterparts(hex::index_of(east.offset()), Orientation::Flat);
+        assert_eq!(counterparts.len(), 6);
+        for (index, turn) in counterparts {
+            let facing = HexDirection::ALL
+                .into_iter()
+                .find(|direction| direction.offset() == hex::cube(index))
+                .unwrap();
+            assert_eq!(
+                turn.tag(AssetTag::CoastSmallE),
+                AssetTag::coast(CoastSize::Small, facing)
+            );
+        }
+    }
+
+    #[test]
+    fn mirrored_coasts_face_the_mirrored_way() {
+        let north_east = HexDirection::NorthEast;
+        let counterparts = symmetry(true, false, 1)
+            .counterparts(hex::index_of(north_east.offset()), Orientation::Pointy);
+        assert_eq!(counterparts.len(), 2);
+        let (index, turn) = counterparts[1];
+        assert_eq!(hex::cube(index), HexDirection::SouthEast.offset());
+        assert_eq!(turn.tag(AssetTag::CoastSmallNE), AssetTag::CoastSmallSE);
+        assert_eq!(turn.tag(AssetTag::PlainsLush), AssetTag::PlainsLush);
+    }
+}
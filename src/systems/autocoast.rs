@@ -0,0 +1,78 @@
+use std::mem;
+
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+
+use crate::{
+    AUTO_COAST_QUEUE, asset_loading::TileCatalog, autocoast::coast_fixes, history::EditHistory,
+    map::HexMap, resources::AutoCoast,
+};
+
+/// Turns auto-coast on or off.
+#[derive(Event)]
+pub struct SetAutoCoastEvent(pub bool);
+
+pub fn flush_auto_coast_queue(mut event_writer: EventWriter<SetAutoCoastEvent>) {
+    let mut queue = AUTO_COAST_QUEUE.lock().unwrap();
+    if !queue.is_empty() {
+        let events = mem::take(&mut *queue);
+        for enabled in events {
+            event_writer.send(SetAutoCoastEvent(enabled));
+        }
+    }
+}
+
+/// Switching auto-coast on fixes up the whole map once, as its own undo step.
+pub fn handle_set_auto_coast_event(
+    mut event_reader: EventReader<SetAutoCoastEvent>,
+    mut auto_coast: ResMut<AutoCoast>,
+    catalog: Option<Res<TileCatalog>>,
+    mut hex_map: ResMut<HexMap>,
+    mut history: ResMut<EditHistory>,
+) {
+    for SetAutoCoastEvent(enabled) in event_reader.read() {
+        let was_enabled = mem::replace(&mut auto_coast.0, *enabled);
+        info!("AutoCoast set to {}", auto_coast.0);
+        if !*enabled || was_enabled {
+            continue;
+        }
+        let Some(catalog) = catalog.as_deref() else {
+            continue;
+        };
+
+        let all: Vec<hgs::TileIndex> = hex_map.iter().map(|(index, _)| *index).collect();
+        history.end_stroke();
+        for (index, data) in coast_fixes(&hex_map, catalog, all) {
+            history.apply(&mut hex_map, index, Some(data));
+        }
+        history.end_stroke();
+        history.take_touched();
+    }
+}
+
+/// Keeps coastlines correct around every hex painted this frame. The beach changes are
+/// amended to the edit that caused them, so one undo reverts both. If that edit cancelled
+/// itself out, they become an undo step of their own.
+pub fn auto_coast_system(
+    auto_coast: Res<AutoCoast>,
+    catalog: Option<Res<TileCatalog>>,
+    mut hex_map: ResMut<HexMap>,
+    mut history: ResMut<EditHistory>,
+) {
+    // Drained even while disabled, so turning auto-coast on doesn't replay old strokes.
+    let touched = history.bypass_change_detection().take_touched();
+    if touched.is_empty() || !auto_coast.0 {
+        return;
+    }
+    let Some(catalog) = catalog.as_deref() else {
+        return;
+    };
+
+    let stroke_open = history.stroke_open();
+    for (index, data) in coast_fixes(&hex_map, catalog, touched) {
+        history.amend(&mut hex_map, index, Some(data));
+    }
+    if !stroke_open {
+        history.end_stroke();
+    }
+}
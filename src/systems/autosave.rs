@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+
+use crate::{
+    autosave::{AUTOSAVE_DELAY_SECS, Autosaver},
+    map::HexMap,
+    map_document::MapDocument,
+};
+
+/// Writes the map to `localStorage` once it has stopped changing for `AUTOSAVE_DELAY_SECS`.
+pub fn autosave_system(
+    hex_map: Res<HexMap>,
+    time: Res<Time>,
+    mut pending_since: Local<Option<f32>>,
+    mut autosaver: Local<Autosaver>,
+) {
+    // The initial fill in `setup` is not an edit worth saving.
+    if hex_map.is_changed() && !hex_map.is_added() {
+        *pending_since = Some(time.elapsed_secs());
+    }
+
+    if let Some(since) = *pending_since
+        && time.elapsed_secs() - since >= AUTOSAVE_DELAY_SECS
+    {
+        autosaver.save(&MapDocument::from_map(&hex_map));
+        *pending_since = None;
+    }
+}
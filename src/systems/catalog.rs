@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use wasm_bindgen::JsValue;
+
+use crate::{
+    TILE_CATALOG_SNAPSHOT,
+    asset_loading::{TileCatalog, TileCatalogHandle, load_tile_image},
+    js_events,
+    map::HexMap,
+    resources::TileImageHandles,
+};
+
+/// Applies the tile catalog once it has loaded (and again whenever it is hot-reloaded):
+/// loads the tile images, publishes the catalog as a resource, tells the page that
+/// `list_tiles` is ready (`tilecatalogloaded` event) and redraws the map.
+pub fn apply_tile_catalog(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<TileCatalog>>,
+    catalogs: Res<Assets<TileCatalog>>,
+    catalog_handle: Res<TileCatalogHandle>,
+    asset_server: Res<AssetServer>,
+    mut tile_image_handles: ResMut<TileImageHandles>,
+    mut hex_map: ResMut<HexMap>,
+) {
+    for event in events.read() {
+        let id = match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => *id,
+            _ => continue,
+        };
+        if id != catalog_handle.0.id() {
+            continue;
+        }
+        let Some(catalog) = catalogs.get(id) else {
+            continue;
+        };
+
+        for tile in &catalog.tiles {
+            if tile.files.is_empty() {
+                warn!("Tile {:?} has no image files", tile.id);
+            }
+            let variants = tile
+                .files
+                .iter()
+                .map(|file| load_tile_image(file, &asset_server))
+                .collect();
+            tile_image_handles.handles.insert(tile.id, variants);
+            tile_image_handles.facings.insert(tile.id, tile.facing());
+        }
+        info!("Loaded tile catalog with {} tiles", catalog.tiles.len());
+
+        commands.insert_resource(catalog.clone());
+        if let Ok(mut snapshot) = TILE_CATALOG_SNAPSHOT.lock() {
+            *snapshot = Some(catalog.clone());
+        }
+        js_events::dispatch("tilecatalogloaded", &JsValue::UNDEFINED);
+        // The hexes were drawn before their images were known.
+        hex_map.bypass_change_detection().mark_all_dirty();
+    }
+}
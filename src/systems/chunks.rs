@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+use std::collections::{HashMap, HashSet};
+
+use crate::chunks::ChunkCoord;
+use crate::components::MainCamera;
+use crate::layout::Orientation;
+use crate::map::HexMap;
+use crate::resources::ChunkMeshes;
+
+/// Extra world units around the view in which chunks are kept, so that small camera
+/// moves along a chunk border don't build and despawn the same meshes over and over.
+const VIEW_MARGIN: f32 = 256.;
+
+/// Chunks that are drawn, because they are in or near the view.
+#[derive(Resource, Default)]
+pub struct VisibleChunks(pub HashSet<ChunkCoord>);
+
+/// Streams chunk meshes in and out as the camera moves.
+///
+/// Chunks entering the view get their hexes marked dirty, so the sync system builds their
+/// meshes; chunks leaving it have their entities despawned. Hexes of chunks out of view
+/// only exist in the `HexMap`.
+pub fn chunk_visibility_system(
+    mut commands: Commands,
+    mut hex_map: ResMut<HexMap>,
+    mut visible: ResMut<VisibleChunks>,
+    mut chunk_meshes: ResMut<ChunkMeshes>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut bounds_cache: Local<(Orientation, HashMap<ChunkCoord, Rect>)>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let Some(view) = view_rect(camera, camera_transform) else {
+        return;
+    };
+    let view = view.inflate(VIEW_MARGIN);
+
+    let orientation = hex_map.orientation();
+    if bounds_cache.0 != orientation {
+        *bounds_cache = (orientation, HashMap::new());
+    }
+
+    let in_view: HashSet<ChunkCoord> = hex_map
+        .chunk_coords()
+        .filter(|coord| {
+            let bounds = bounds_cache
+                .1
+                .entry(*coord)
+                .or_insert_with(|| coord.bounds(orientation));
+            !bounds.intersect(view).is_empty()
+        })
+        .collect();
+    if in_view == visible.0 {
+        return;
+    }
+
+    for coord in visible.0.difference(&in_view) {
+        if let Some(entity) = chunk_meshes.0.remove(coord) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    let entering: Vec<hgs::TileIndex> = in_view
+        .difference(&visible.0)
+        .flat_map(|coord| hex_map.chunk_tiles(*coord))
+        .collect();
+    // Building meshes is not an edit of the document.
+    hex_map.bypass_change_detection().mark_dirty(entering);
+
+    visible.0 = in_view;
+}
+
+/// The part of the world shown by the camera.
+fn view_rect(camera: &Camera, camera_transform: &GlobalTransform) -> Option<Rect> {
+    let viewport = camera.logical_viewport_rect()?;
+    let min = camera
+        .viewport_to_world_2d(camera_transform, viewport.min)
+        .ok()?;
+    let max = camera
+        .viewport_to_world_2d(camera_transform, viewport.max)
+        .ok()?;
+    Some(Rect::from_corners(min, max))
+}
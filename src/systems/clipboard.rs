@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use wasm_bindgen::JsValue;
+
+use crate::{
+    CLIPBOARD_QUEUE,
+    clipboard::{Clipboard, Pattern, read_stamps, write_stamps},
+    js_events,
+    map::HexMap,
+    resources::{PaintTool, Selection},
+    systems::tools::{SetPaintToolEvent, ctrl_pressed},
+};
+
+/// Event for the clipboard and stamps, sent from the UI or the keyboard.
+#[derive(Event, Debug, Clone)]
+pub enum ClipboardEvent {
+    /// Copy the selected hexes into the clipboard.
+    Copy,
+    /// Turn the pasted pattern counterclockwise by this many 60° steps.
+    Rotate(u8),
+    /// Mirror the pasted pattern north to south.
+    Mirror,
+    /// Save the clipboard as a named stamp, replacing any stamp with the same name.
+    SaveStamp(String),
+    /// Load a stamp into the clipboard and switch to the paste tool.
+    UseStamp(String),
+    DeleteStamp(String),
+}
+
+/// Drains clipboard requests from the JS queue.
+pub fn flush_clipboard_queue(mut writer: EventWriter<ClipboardEvent>) {
+    if let Ok(mut queue) = CLIPBOARD_QUEUE.lock() {
+        for event in queue.drain(..) {
+            writer.send(event);
+        }
+    }
+}
+
+/// Ctrl+C copies the selection and Ctrl+V switches to the paste tool.
+/// Q and E turn the pasted pattern counter- and clockwise and F mirrors it.
+pub fn clipboard_keyboard_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut writer: EventWriter<ClipboardEvent>,
+    mut tool_writer: EventWriter<SetPaintToolEvent>,
+) {
+    if ctrl_pressed(&keys) {
+        if keys.just_pressed(KeyCode::KeyC) {
+            writer.send(ClipboardEvent::Copy);
+        }
+        if keys.just_pressed(KeyCode::KeyV) {
+            tool_writer.send(SetPaintToolEvent(PaintTool::Paste));
+        }
+        return;
+    }
+    if keys.just_pressed(KeyCode::KeyQ) {
+        writer.send(ClipboardEvent::Rotate(1));
+    }
+    if keys.just_pressed(KeyCode::KeyE) {
+        writer.send(ClipboardEvent::Rotate(5));
+    }
+    if keys.just_pressed(KeyCode::KeyF) {
+        writer.send(ClipboardEvent::Mirror);
+    }
+}
+
+/// Applies clipboard and stamp requests. Changes to the saved stamps are announced with a
+/// `stampschanged` event carrying the stamp names.
+pub fn handle_clipboard_event(
+    mut events: EventReader<ClipboardEvent>,
+    hex_map: Res<HexMap>,
+    selection: Res<Selection>,
+    mut clipboard: ResMut<Clipboard>,
+    mut tool_writer: EventWriter<SetPaintToolEvent>,
+) {
+    for event in events.read() {
+        match event {
+            ClipboardEvent::Copy => {
+                let pattern = Pattern::copy(&hex_map, selection.0.iter().copied());
+                if pattern.is_empty() {
+                    warn!("Nothing to copy: select some painted hexes first");
+                    continue;
+                }
+                clipboard.set(pattern);
+            }
+            ClipboardEvent::Rotate(steps) => clipboard.rotate(*steps),
+            ClipboardEvent::Mirror => clipboard.toggle_mirror(),
+            ClipboardEvent::SaveStamp(name) => {
+                if clipboard.pattern.is_empty() {
+                    warn!("Nothing to save: copy a selection first");
+                    continue;
+                }
+                let mut stamps = read_stamps();
+                stamps.insert(name.clone(), clipboard.oriented());
+                write_stamps(&stamps);
+                notify_stamps_changed();
+            }
+            ClipboardEvent::UseStamp(name) => match read_stamps().remove(name) {
+                Some(pattern) => {
+                    clipboard.set(pattern);
+                    tool_writer.send(SetPaintToolEvent(PaintTool::Paste));
+                }
+                None => warn!("No stamp named {}", name),
+            },
+            ClipboardEvent::DeleteStamp(name) => {
+                let mut stamps = read_stamps();
+                if stamps.remove(name).is_some() {
+                    write_stamps(&stamps);
+                    notify_stamps_changed();
+                }
+            }
+        }
+    }
+}
+
+fn notify_stamps_changed() {
+    let names: Vec<String> = read_stamps().into_keys().collect();
+    let names = serde_wasm_bindgen::to_value(&names).unwrap_or(JsValue::NULL);
+    js_events::dispatch("stampschanged", &names);
+}
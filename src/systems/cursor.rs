@@ -2,41 +2,31 @@ use bevy::input::ButtonInput;
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
-use hexgridspiral as hgs;
 
-use crate::components::MainCamera;
-use crate::components::TileMarker;
-use crate::resources::SelectedHex;
-use crate::resources::{TileImageHandles, WorldCoords};
-use crate::systems::setup::spawn_tile_with_index;
-use crate::tile_config::image_size;
-use crate::tile_config::step_size;
+use crate::components::{HoverMarker, MainCamera};
+use crate::hex;
+use crate::layout::tile_depth;
+use crate::map::HexMap;
+use crate::resources::{HoveredTile, Selection, TileImageHandles, WorldCoords};
+use crate::systems::selection::SELECTED_TINT;
 
-/// Tracks the user's cursor and updates the hovered tile.
-/// Also updates tile sprite and transform to highlight selection.
+/// Tracks the user's cursor and updates the hovered tile. The hovered tile is popped out
+/// by `hover_highlight_system`; painting is done by `paint_system`.
+#[allow(clippy::too_many_arguments)]
 pub fn cursor_system(
     mut coords: ResMut<WorldCoords>,
-    tile_image_handles: Res<TileImageHandles>,
-    selected_hex: Res<SelectedHex>,
-    buttons: Res<ButtonInput<MouseButton>>,
+    mut hovered: ResMut<HoveredTile>,
+    hex_map: Res<HexMap>,
     keys: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
     mut mouse_wheel: EventReader<MouseWheel>,
-    mut commands: Commands,
-    // query for TileMarkers
 
     // query to get the window (so we can read the current cursor position)
     q_window: Query<&Window, With<PrimaryWindow>>,
-    mut param_set: ParamSet<(
-        // Param 0: Access to camera
-        Query<(&Camera, &GlobalTransform, &mut Transform), With<MainCamera>>,
-        // Param 1: Access to tiles with Transform
-        Query<(&mut TileMarker, &mut Sprite, &mut Transform)>,
-    )>,
+    mut camera_query: Query<(&Camera, &GlobalTransform, &mut Transform), With<MainCamera>>,
 ) {
     // --- Mouse Wheel Zoom ---
     {
-        let mut camera_query = param_set.p0();
         let (_, _, mut camera_transform_mut) = camera_query.single_mut();
 
         let mut zoom_delta = 0.0;
@@ -51,7 +41,6 @@ pub fn cursor_system(
     }
     // --- Keyboard Movement ---
     {
-        let mut camera_query = param_set.p0();
         let (_, _, mut camera_transform_mut) = camera_query.single_mut();
 
         let mut movement = Vec3::ZERO;
@@ -76,7 +65,6 @@ pub fn cursor_system(
     }
     // --- Cursor tracking ---
     let world_position = {
-        let camera_query = param_set.p0();
         let (camera, camera_global, _) = camera_query.single();
         let window = q_window.single();
 
@@ -85,67 +73,53 @@ pub fn cursor_system(
             .and_then(|cursor| camera.viewport_to_world(camera_global, cursor).ok())
             .map(|ray| ray.origin.truncate())
     };
-    let (camera, camera_global, _) = param_set.p0().single();
-    let window = q_window.single();
 
     if let Some(world_pos) = world_position {
         coords.0 = world_pos;
 
-        let (step_x, step_y) = step_size();
-        let (image_w, image_h) = image_size();
-
         // log::warn!("World coords: {}/{}", world_position.x, world_position.y);
-        let new_hover_tile: hgs::HGSTile = hgs::CCTile::from_irregular_pixel(
-            (world_pos.y as f64, -world_pos.x as f64),
-            (0., 0.),
-            (step_x, step_y),
-        )
-        .into();
-
-        let selected_index: hgs::TileIndex = new_hover_tile.spiral_index();
-        let mut missing_tile = true;
+        let selected_index = hex_map.orientation().hex_at(world_pos);
         // log::warn!("Selected Index: {}", selected_index.0);
+        hovered.index = Some(selected_index);
+    } else {
+        hovered.index = None;
+    }
+}
 
-        // --- TILE UPDATE ---
-        {
-            let mut tile_query = param_set.p1();
-
-            for (mut tile_marker, mut sprite, mut transform) in tile_query.iter_mut() {
-                if selected_index.0 == tile_marker.index.0 {
-                    missing_tile = false;
-                    // tile_marker.tag = AssetTag::BaseLush;
-
-                    // Pop-out effect
-                    transform.translation.z += 1.0;
-                    transform.scale = Vec3::splat(1.1);
-
-                    // If clicked, apply hex
-                    if buttons.pressed(MouseButton::Left) {
-                        tile_marker.tag = selected_hex.0;
-
-                        if let Some(handle) = tile_image_handles.handles.get(&tile_marker.tag) {
-                            sprite.image = handle.clone();
-                        }
-                    }
-                } else {
-                    // Reset pop-out effect
-                    transform.translation.z = transform.translation.y * -0.0001;
-                    transform.scale = Vec3::ONE;
-                }
-            }
+/// Pops the hovered tile out of its chunk: the `HoverMarker` sprite draws a copy of it,
+/// slightly larger and above its neighbours. Hidden while no existing hex is hovered.
+pub fn hover_highlight_system(
+    hovered: Res<HoveredTile>,
+    hex_map: Res<HexMap>,
+    selection: Res<Selection>,
+    tile_image_handles: Res<TileImageHandles>,
+    mut marker_query: Query<(&mut Sprite, &mut Transform, &mut Visibility), With<HoverMarker>>,
+) {
+    let Ok((mut sprite, mut transform, mut visibility)) = marker_query.get_single_mut() else {
+        return;
+    };
+    let Some((index, data)) = hovered
+        .index
+        .and_then(|index| Some((index, hex_map.get(index)?)))
+    else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
 
-            // --- Spawn New Tile if Not Found
-            if buttons.pressed(MouseButton::Left) && missing_tile {
-                if let Some(handle) = tile_image_handles.handles.get(&selected_hex.0) {
-                    spawn_tile_with_index(
-                        &mut commands,
-                        &selected_index,
-                        (image_w, image_h),
-                        (step_x, step_y),
-                        handle.clone(),
-                    );
-                }
-            }
-        }
-    }
+    let orientation = hex_map.orientation();
+    let facing = tile_image_handles.facing(data.tag);
+    let position = orientation.world_position(hex::cube(index));
+    sprite.image = tile_image_handles.for_hex(hex_map.seed(), index, data);
+    sprite.flip_x = facing.flip_x;
+    sprite.color = if selection.0.contains(&index) {
+        SELECTED_TINT
+    } else {
+        Color::WHITE
+    };
+    *transform = Transform {
+        translation: position.extend(tile_depth(position.y) + 1.0),
+        rotation: orientation.tile_rotation(facing),
+        scale: Vec3::splat(1.1),
+    };
+    visibility.set_if_neq(Visibility::Inherited);
 }
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+use crate::{HISTORY_QUEUE, history::EditHistory, map::HexMap, systems::tools::ctrl_pressed};
+
+/// Event requesting an undo or redo step.
+#[derive(Event, Debug, Clone, Copy)]
+pub enum HistoryEvent {
+    Undo,
+    Redo,
+}
+
+/// Drains undo/redo requests from the JS queue and injects them into Bevy's event system.
+pub fn flush_history_queue(mut writer: EventWriter<HistoryEvent>) {
+    if let Ok(mut queue) = HISTORY_QUEUE.lock() {
+        for event in queue.drain(..) {
+            writer.send(event);
+        }
+    }
+}
+
+/// Maps Ctrl+Z to undo and Ctrl+Shift+Z (or Ctrl+Y) to redo.
+pub fn history_keyboard_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut writer: EventWriter<HistoryEvent>,
+) {
+    if !ctrl_pressed(&keys) {
+        return;
+    }
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    if keys.just_pressed(KeyCode::KeyZ) {
+        writer.send(if shift {
+            HistoryEvent::Redo
+        } else {
+            HistoryEvent::Undo
+        });
+    } else if keys.just_pressed(KeyCode::KeyY) {
+        writer.send(HistoryEvent::Redo);
+    }
+}
+
+pub fn handle_history_event(
+    mut events: EventReader<HistoryEvent>,
+    mut history: ResMut<EditHistory>,
+    mut hex_map: ResMut<HexMap>,
+) {
+    for event in events.read() {
+        let applied = match event {
+            HistoryEvent::Undo => history.undo(&mut hex_map),
+            HistoryEvent::Redo => history.redo(&mut hex_map),
+        };
+        if !applied {
+            info!("Nothing to {:?}", event);
+        }
+    }
+}
@@ -1,8 +1,24 @@
+use std::collections::{HashMap, HashSet};
 use std::mem;
 
+use bevy::color::palettes::css;
 use bevy::prelude::*;
 
-use crate::{LABEL_QUEUE, resources::ShowTileLabels};
+use crate::{
+    LABEL_MODE_QUEUE, LABEL_QUEUE,
+    chunks::ChunkCoord,
+    components::MainCamera,
+    hex,
+    labels::{LabelFormat, LabelMode},
+    layout::tile_depth,
+    map::HexMap,
+    resources::ShowTileLabels,
+    systems::{chunks::VisibleChunks, sync::ChunkChangedEvent},
+};
+
+/// Largest camera scale at which tile labels are shown. Zoomed out further they are too
+/// small to read, and too many to spawn.
+const LABEL_MAX_SCALE: f32 = 2.0;
 
 /// An event to toggle whether tile labels should be visible.
 #[derive(Event)]
@@ -28,15 +44,95 @@ pub fn handle_toggle_tile_labels_event(
     }
 }
 
-pub fn toggle_tile_labels_system(
+/// An event to change which coordinates tile labels show.
+#[derive(Event)]
+pub struct SetLabelModeEvent(pub LabelMode);
+
+pub fn flush_label_mode_queue(mut event_writer: EventWriter<SetLabelModeEvent>) {
+    let mut queue = LABEL_MODE_QUEUE.lock().unwrap();
+    if !queue.is_empty() {
+        let events = mem::take(&mut *queue);
+        for mode in events {
+            event_writer.send(SetLabelModeEvent(mode));
+        }
+    }
+}
+
+pub fn handle_set_label_mode_event(
+    mut event_reader: EventReader<SetLabelModeEvent>,
+    mut label_format: ResMut<LabelFormat>,
+) {
+    for SetLabelModeEvent(mode) in event_reader.read() {
+        label_format.mode = *mode;
+        info!("LabelMode set to {:?}", mode);
+    }
+}
+
+/// Keeps a label on every hex of the chunks in view, while labels are switched on and the
+/// camera is zoomed in far enough to read them.
+///
+/// Labels are spawned per chunk. A chunk's labels are redone when the sync system rebuilds
+/// it, and every label when the label mode changes or, in offset mode, when the map's
+/// top-left corner moves.
+#[allow(clippy::too_many_arguments)]
+pub fn tile_label_system(
+    mut commands: Commands,
+    hex_map: Res<HexMap>,
     show_labels: Res<ShowTileLabels>,
-    mut label_query: Query<&mut Visibility, With<Text2d>>,
+    visible_chunks: Res<VisibleChunks>,
+    mut label_format: ResMut<LabelFormat>,
+    mut changed_chunks: EventReader<ChunkChangedEvent>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    mut labels: Local<HashMap<ChunkCoord, Vec<Entity>>>,
 ) {
-    for mut visibility in &mut label_query {
-        *visibility = if show_labels.0 {
-            Visibility::Visible
-        } else {
-            Visibility::Hidden
-        };
+    let mut relabel = label_format.is_changed();
+    if label_format.mode == LabelMode::Offset {
+        let origin = hex_map.offset_origin();
+        if origin != label_format.offset_origin {
+            // Not a settings change, so don't trigger another relabel next frame.
+            label_format.bypass_change_detection().offset_origin = origin;
+            relabel = true;
+        }
+    }
+    let changed: HashSet<ChunkCoord> = changed_chunks.read().map(|event| event.0).collect();
+
+    let readable = camera_query
+        .get_single()
+        .is_ok_and(|transform| transform.scale.x <= LABEL_MAX_SCALE);
+    let shown = show_labels.0 && readable;
+    labels.retain(|coord, entities| {
+        let keep =
+            shown && !relabel && visible_chunks.0.contains(coord) && !changed.contains(coord);
+        if !keep {
+            for entity in entities.drain(..) {
+                commands.entity(entity).despawn();
+            }
+        }
+        keep
+    });
+    if !shown {
+        return;
+    }
+
+    let orientation = hex_map.orientation();
+    for &coord in &visible_chunks.0 {
+        if labels.contains_key(&coord) {
+            continue;
+        }
+        let entities = hex_map
+            .chunk_tiles(coord)
+            .map(|index| {
+                let position = orientation.world_position(hex::cube(index));
+                commands
+                    .spawn((
+                        Text2d::new(label_format.text(index, orientation)),
+                        TextColor(css::DIM_GRAY.into()),
+                        // Above the tiles around it, but below the hover pop-out.
+                        Transform::from_translation(position.extend(tile_depth(position.y) + 0.5)),
+                    ))
+                    .id()
+            })
+            .collect();
+        labels.insert(coord, entities);
     }
 }
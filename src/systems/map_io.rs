@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+use crate::{
+    IMPORT_QUEUE, MAP_SNAPSHOT, history::EditHistory, map::HexMap, map_document::MapDocument,
+};
+
+/// Event carrying a parsed map that should replace the current one.
+#[derive(Event)]
+pub struct ImportMapEvent(pub MapDocument);
+
+/// Drains imported maps from the JS queue and injects them into Bevy's event system.
+pub fn flush_map_import_queue(mut writer: EventWriter<ImportMapEvent>) {
+    if let Ok(mut queue) = IMPORT_QUEUE.lock() {
+        for document in queue.drain(..) {
+            writer.send(ImportMapEvent(document));
+        }
+    }
+}
+
+/// Replaces the whole map with an imported document, as a single undo step.
+/// Hexes that are not part of the document are removed by the sync system.
+pub fn handle_import_map_event(
+    mut events: EventReader<ImportMapEvent>,
+    mut hex_map: ResMut<HexMap>,
+    mut history: ResMut<EditHistory>,
+) {
+    for event in events.read() {
+        hex_map.set_orientation(event.0.orientation);
+        history.replace_all(&mut hex_map, event.0.seed, event.0.tiles());
+        info!("Imported map with {} tiles", event.0.tiles.len());
+    }
+}
+
+/// Keeps the snapshot read by `export_map` up to date with the map. Building it walks and
+/// sorts the whole map, so while a stroke is being painted it waits for the stroke to end.
+pub fn publish_map_snapshot(
+    hex_map: Res<HexMap>,
+    history: Res<EditHistory>,
+    mut pending: Local<bool>,
+) {
+    *pending |= hex_map.is_changed();
+    if !*pending || history.stroke_open() {
+        return;
+    }
+    if let Ok(mut snapshot) = MAP_SNAPSHOT.lock() {
+        *snapshot = MapDocument::from_map(&hex_map);
+    }
+    *pending = false;
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+use std::collections::HashSet;
+
+use crate::{
+    MAP_SHAPE_QUEUE,
+    asset_loading::AssetTag,
+    history::EditHistory,
+    layout::Orientation,
+    map::{HexData, HexMap},
+    map_shape::MapShape,
+    random::random_seed,
+};
+
+/// Requests from the map settings in the toolbox.
+#[derive(Event, Debug, Clone, Copy)]
+pub enum MapShapeEvent {
+    /// Replace the map with blank hexes in the given shape.
+    New(MapShape),
+    /// Fit the map to the given shape: hexes outside it are removed, missing ones added blank.
+    Resize(MapShape),
+    /// Draw the map flat-top or pointy-top. The hexes themselves stay the same.
+    SetOrientation(Orientation),
+}
+
+/// Drains map shape requests from the JS queue and injects them into Bevy's event system.
+pub fn flush_map_shape_queue(mut writer: EventWriter<MapShapeEvent>) {
+    if let Ok(mut queue) = MAP_SHAPE_QUEUE.lock() {
+        for event in queue.drain(..) {
+            writer.send(event);
+        }
+    }
+}
+
+/// Creates or resizes the map, each as a single undo step, or changes its orientation.
+pub fn handle_map_shape_event(
+    mut events: EventReader<MapShapeEvent>,
+    mut hex_map: ResMut<HexMap>,
+    mut history: ResMut<EditHistory>,
+) {
+    for event in events.read() {
+        match *event {
+            MapShapeEvent::New(shape) => {
+                let orientation = hex_map.orientation();
+                let tiles = blank_tiles(shape, orientation);
+                history.replace_all(&mut hex_map, random_seed(), tiles);
+                info!("Created new map: {:?}", shape);
+            }
+            MapShapeEvent::Resize(shape) => {
+                let inside: HashSet<_> = shape.indices(hex_map.orientation()).into_iter().collect();
+                let outside: Vec<_> = hex_map
+                    .iter()
+                    .map(|(index, _)| *index)
+                    .filter(|index| !inside.contains(index))
+                    .collect();
+
+                history.end_stroke();
+                for index in outside {
+                    history.apply(&mut hex_map, index, None);
+                }
+                for index in inside {
+                    if !hex_map.contains(index) {
+                        history.apply(&mut hex_map, index, Some(HexData::new(AssetTag::Blank)));
+                    }
+                }
+                history.end_stroke();
+                info!("Resized map to {:?}", shape);
+            }
+            MapShapeEvent::SetOrientation(orientation) => {
+                hex_map.set_orientation(orientation);
+                info!("Map orientation set to {:?}", orientation);
+            }
+        }
+    }
+}
+
+/// Blank hexes filling a shape, used for new maps.
+pub fn blank_tiles(
+    shape: MapShape,
+    orientation: Orientation,
+) -> impl Iterator<Item = (hgs::TileIndex, HexData)> {
+    shape
+        .indices(orientation)
+        .into_iter()
+        .map(|index| (index, HexData::new(AssetTag::Blank)))
+}
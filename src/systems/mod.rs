@@ -1,9 +1,19 @@
+pub mod autocoast;
+pub mod autosave;
+pub mod catalog;
+pub mod chunks;
+pub mod clipboard;
 pub mod cursor;
+pub mod history;
 pub mod labels;
+pub mod map_io;
+pub mod map_shape;
+pub mod paint;
+pub mod preview;
+pub mod scatter;
+pub mod selection;
 pub mod setup;
+pub mod symmetry;
+pub mod sync;
 pub mod tools;
-
-// Re-export frequently used systems or types for easier access from main.rs
-pub use cursor::cursor_system;
-pub use setup::setup;
-pub use tools::{HexSelectedEvent, flush_click_events_system, on_hex_selected};
+pub mod variants;
@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+use std::collections::{HashMap, HashSet};
+
+use crate::asset_loading::AssetTag;
+use crate::clipboard::Clipboard;
+use crate::fill::flood_region;
+use crate::hex;
+use crate::history::EditHistory;
+use crate::map::{HexData, HexMap};
+use crate::resources::{
+    ActivePaintTool, Brush, FillSettings, HoveredTile, PaintTool, PreviewHexes, SelectedHex,
+};
+use crate::symmetry::Symmetry;
+use crate::systems::tools::alt_pressed;
+
+/// Applies the active paint tool at the hovered hex and previews what it would paint.
+/// Line, ring and hexagon drags are painted as one step on release. Hexagons are
+/// previewed by their outline, lines and rings in full.
+/// The select and scatter tools are left to `select_system` and `scatter_system`. With
+/// `Symmetry` on, every painted and previewed hex is repeated at its symmetric
+/// counterparts, with coasts turned to match.
+/// Missing hexes are simply added to the map; the sync system draws them.
+/// With `AssetTag::None` selected the tools erase: hexes are removed from the map, and the
+/// sync system redraws their chunks without them.
+#[allow(clippy::too_many_arguments)]
+pub fn paint_system(
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    hovered: Res<HoveredTile>,
+    tool: Res<ActivePaintTool>,
+    selected_hex: Res<SelectedHex>,
+    brush: Res<Brush>,
+    fill: Res<FillSettings>,
+    mut hex_map: ResMut<HexMap>,
+    mut history: ResMut<EditHistory>,
+    mut preview: ResMut<PreviewHexes>,
+    clipboard: Res<Clipboard>,
+    symmetry: Res<Symmetry>,
+    // Hex under the cursor when the brush last painted, while the button is held.
+    mut stroke_end: Local<Option<hgs::TileIndex>>,
+    // Hex where a line, ring or hexagon drag started, while the button is held.
+    mut drag_start: Local<Option<hgs::TileIndex>>,
+) {
+    let tag = selected_hex.0;
+    let after = paint_data(tag);
+    // Alt+click belongs to the eyedropper.
+    let painting = !alt_pressed(&keys);
+    // What the tool paints this frame, and what it previews. The select and scatter tools
+    // preview their own hexes.
+    let mut edits: Vec<(hgs::TileIndex, Option<HexData>)> = Vec::new();
+    let shown: Option<Vec<(hgs::TileIndex, AssetTag)>> = match (hovered.index, tool.0) {
+        (_, PaintTool::Select | PaintTool::Scatter) => None,
+        (None, _) => Some(Vec::new()),
+        (Some(index), PaintTool::Brush) => {
+            // Every hex under the brush is painted.
+            if painting && buttons.pressed(MouseButton::Left) {
+                let from = stroke_end.unwrap_or(index);
+                edits.extend(
+                    swept(from, index, brush.radius)
+                        .into_iter()
+                        .map(|index| (index, after)),
+                );
+                *stroke_end = Some(index);
+            }
+            Some(
+                hex::within(index, brush.radius as i64)
+                    .map(|index| (index, tag))
+                    .collect(),
+            )
+        }
+        (Some(index), PaintTool::Fill) => {
+            if painting && buttons.just_pressed(MouseButton::Left) {
+                match flood_region(&hex_map, index, fill.max_distance) {
+                    Ok(region) => edits.extend(region.into_iter().map(|index| (index, after))),
+                    Err(err) => log::warn!("{}", err),
+                }
+            }
+            Some(vec![(index, tag)])
+        }
+        (Some(index), PaintTool::Paste) => {
+            let pattern = clipboard.oriented();
+            if painting && buttons.just_pressed(MouseButton::Left) {
+                if pattern.is_empty() {
+                    log::warn!("Nothing to paste: copy a selection first");
+                }
+                edits.extend(
+                    pattern
+                        .placed(index)
+                        .map(|(index, data)| (index, Some(data))),
+                );
+            }
+            Some(
+                pattern
+                    .placed(index)
+                    .map(|(index, data)| (index, data.tag))
+                    .collect(),
+            )
+        }
+        (Some(index), shape_tool) => {
+            // Shapes are previewed while dragging and only painted on release.
+            if painting && buttons.just_pressed(MouseButton::Left) {
+                *drag_start = Some(index);
+            }
+            let shape = match *drag_start {
+                Some(start) => drag_shape(shape_tool, start, index, false),
+                None => vec![index],
+            };
+            let released = buttons.just_released(MouseButton::Left);
+            if let Some(start) = drag_start.take_if(|_| released) {
+                let filled = drag_shape(shape_tool, start, index, true);
+                edits.extend(filled.into_iter().map(|index| (index, after)));
+            }
+            Some(shape.into_iter().map(|index| (index, tag)).collect())
+        }
+    };
+
+    let orientation = hex_map.orientation();
+    for (index, data) in edits {
+        for (index, turn) in symmetry.counterparts(index, orientation) {
+            let data = data.map(|data| {
+                let tag = turn.tag(data.tag);
+                HexData { tag, ..data }.painted_over(hex_map.get(index))
+            });
+            history.apply(&mut hex_map, index, data);
+        }
+    }
+    if let Some(shown) = shown {
+        let mut shown: HashMap<hgs::TileIndex, AssetTag> = shown
+            .into_iter()
+            .flat_map(|(index, tag)| {
+                let counterparts = symmetry.counterparts(index, orientation);
+                counterparts
+                    .into_iter()
+                    .map(move |(index, turn)| (index, turn.tag(tag)))
+            })
+            .collect();
+        // Outline the symmetry centre, so it's clear what the copies turn around.
+        if symmetry.is_active() && !shown.is_empty() {
+            shown.entry(symmetry.centre).or_insert(AssetTag::Outline);
+        }
+        preview.set_if_neq(PreviewHexes(shown));
+    }
+
+    if !buttons.pressed(MouseButton::Left) || hovered.index.is_none() {
+        *stroke_end = None;
+    }
+    // Releasing off the map cancels a shape.
+    if !buttons.pressed(MouseButton::Left) {
+        *drag_start = None;
+    }
+    // A held button is one stroke, and therefore one undo step.
+    if buttons.just_released(MouseButton::Left) {
+        history.end_stroke();
+    }
+}
+
+/// Hexes under a brush of `radius` swept from `from` to `to`. Fast drags move the cursor
+/// several hexes per frame, so the brush is swept along the hex line between them.
+pub fn swept(from: hgs::TileIndex, to: hgs::TileIndex, radius: u8) -> HashSet<hgs::TileIndex> {
+    hex::line(hex::cube(from), hex::cube(to))
+        .into_iter()
+        .flat_map(|centre| hex::range(centre, radius as i64))
+        .map(hex::index_of)
+        .collect()
+}
+
+/// Largest radius of a dragged ring or hexagon: the largest hexagon that fits within
+/// `MAX_FILL_HEXES`.
+const MAX_SHAPE_RADIUS: i64 = 182;
+
+/// Hexes covered by dragging a line, ring or hexagon from `start` to `end`.
+/// Rings and hexagons are centred on `start` and reach out to `end`, but no further than
+/// `MAX_SHAPE_RADIUS`. Unless `filled`, a hexagon is just its outline ring.
+fn drag_shape(
+    tool: PaintTool,
+    start: hgs::TileIndex,
+    end: hgs::TileIndex,
+    filled: bool,
+) -> Vec<hgs::TileIndex> {
+    let (start, end) = (hex::cube(start), hex::cube(end));
+    let radius = hex::distance(start, end).min(MAX_SHAPE_RADIUS);
+    let cubes = match tool {
+        PaintTool::Hexagon if filled => hex::range(start, radius),
+        PaintTool::Ring | PaintTool::Hexagon => hex::ring(start, radius),
+        _ => hex::line(start, end),
+    };
+    cubes.into_iter().map(hex::index_of).collect()
+}
+
+/// What painting a tag writes into the map: `None` removes the hex. Hexes that already
+/// show the tag keep their variant; see `HexData::painted_over`.
+pub fn paint_data(tag: AssetTag) -> Option<HexData> {
+    (tag != AssetTag::None).then(|| HexData::new(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fill::MAX_FILL_HEXES;
+
+    #[test]
+    fn largest_hexagon_fits_the_fill_limit() {
+        let hexes = |radius: i64| (3 * radius * (radius + 1) + 1) as usize;
+        assert!(hexes(MAX_SHAPE_RADIUS) <= MAX_FILL_HEXES);
+        assert!(hexes(MAX_SHAPE_RADIUS + 1) > MAX_FILL_HEXES);
+    }
+
+    #[test]
+    fn dragged_shapes_are_clamped() {
+        let start = hex::index_of((0, 0, 0));
+        let far = hex::index_of(hex::scale(hex::HexDirection::East.offset(), 500));
+        let ring = drag_shape(PaintTool::Ring, start, far, false);
+        assert_eq!(ring.len() as i64, 6 * MAX_SHAPE_RADIUS);
+        assert!(
+            ring.iter()
+                .all(|index| hex::distance((0, 0, 0), hex::cube(*index)) == MAX_SHAPE_RADIUS)
+        );
+        let outline = drag_shape(PaintTool::Hexagon, start, far, false);
+        assert_eq!(outline.len(), ring.len());
+        let hexagon = drag_shape(PaintTool::Hexagon, start, far, true);
+        assert!(hexagon.len() <= MAX_FILL_HEXES);
+        let line = drag_shape(PaintTool::Line, start, far, false);
+        assert_eq!(line.len(), 501);
+    }
+}
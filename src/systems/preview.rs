@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+use std::collections::HashMap;
+
+use crate::asset_loading::AssetTag;
+use crate::components::PreviewMarker;
+use crate::hex;
+use crate::map::{HexData, HexMap};
+use crate::resources::{PreviewHexes, TileImageHandles};
+use crate::tile_config::image_size;
+
+/// Depth of preview sprites: above every tile, including the popped-out hovered one.
+const PREVIEW_DEPTH: f32 = 2.;
+const PREVIEW_ALPHA: f32 = 0.6;
+
+/// Draws `PreviewHexes` as translucent sprites on top of the map.
+///
+/// Sprites are kept per hex and only spawned, updated or despawned when the preview changes.
+/// Erasing (`AssetTag::None`) is previewed with the hex outline.
+pub fn preview_system(
+    mut commands: Commands,
+    preview: Res<PreviewHexes>,
+    hex_map: Res<HexMap>,
+    tile_image_handles: Res<TileImageHandles>,
+    mut sprites: Local<HashMap<hgs::TileIndex, Entity>>,
+    mut query: Query<(&mut Sprite, &mut Transform), With<PreviewMarker>>,
+) {
+    if !preview.is_changed() {
+        return;
+    }
+
+    sprites.retain(|index, entity| {
+        let keep = preview.0.contains_key(index);
+        if !keep {
+            commands.entity(*entity).despawn();
+        }
+        keep
+    });
+
+    let orientation = hex_map.orientation();
+    let (width, height) = image_size();
+    for (&index, &tag) in &preview.0 {
+        let tag = if tag == AssetTag::None {
+            AssetTag::Outline
+        } else {
+            tag
+        };
+        let image = tile_image_handles.for_hex(hex_map.seed(), index, &HexData::new(tag));
+        let facing = tile_image_handles.facing(tag);
+        let transform = Transform {
+            translation: orientation
+                .world_position(hex::cube(index))
+                .extend(PREVIEW_DEPTH),
+            rotation: orientation.tile_rotation(facing),
+            ..default()
+        };
+
+        if let Some((mut sprite, mut sprite_transform)) = sprites
+            .get(&index)
+            .and_then(|entity| query.get_mut(*entity).ok())
+        {
+            sprite.image = image;
+            sprite.flip_x = facing.flip_x;
+            *sprite_transform = transform;
+            continue;
+        }
+        let entity = commands
+            .spawn((
+                Sprite {
+                    image,
+                    color: Color::srgba(1., 1., 1., PREVIEW_ALPHA),
+                    custom_size: Some(Vec2::new(width as f32, height as f32)),
+                    flip_x: facing.flip_x,
+                    ..default()
+                },
+                transform,
+                PreviewMarker,
+            ))
+            .id();
+        sprites.insert(index, entity);
+    }
+}
@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+use std::collections::HashMap;
+
+use crate::SCATTER_QUEUE;
+use crate::asset_loading::AssetTag;
+use crate::hex;
+use crate::history::EditHistory;
+use crate::layout::Orientation;
+use crate::map::HexMap;
+use crate::random::{SeededRng, random_seed};
+use crate::resources::{
+    ActivePaintTool, Brush, HoveredTile, PaintTool, PreviewHexes, ScatterSettings,
+};
+use crate::symmetry::Symmetry;
+use crate::systems::paint::{paint_data, swept};
+use crate::systems::tools::alt_pressed;
+
+/// Event changing the scatter brush, sent from the UI.
+#[derive(Event, Debug, Clone)]
+pub enum ScatterEvent {
+    /// Tiles with relative weights; see `ScatterSettings::palette`.
+    SetPalette(Vec<(AssetTag, u32)>),
+    /// Percentage of hexes under the brush that get painted.
+    SetDensity(u8),
+}
+
+/// Drains scatter brush changes from the JS queue.
+pub fn flush_scatter_queue(mut writer: EventWriter<ScatterEvent>) {
+    if let Ok(mut queue) = SCATTER_QUEUE.lock() {
+        for event in queue.drain(..) {
+            writer.send(event);
+        }
+    }
+}
+
+pub fn handle_scatter_event(
+    mut events: EventReader<ScatterEvent>,
+    mut settings: ResMut<ScatterSettings>,
+) {
+    for event in events.read() {
+        match event {
+            ScatterEvent::SetPalette(palette) => settings.palette.clone_from(palette),
+            ScatterEvent::SetDensity(density) => settings.density = *density,
+        }
+    }
+}
+
+/// Paints random tiles from the scatter palette under the brush, swept like the normal
+/// brush. Each stroke draws its own seed from a seeded generator, and picks per hex from
+/// that seed. The seed of the next stroke is drawn ahead of time, so the preview shows
+/// exactly what a click would paint.
+#[allow(clippy::too_many_arguments)]
+pub fn scatter_system(
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    hovered: Res<HoveredTile>,
+    tool: Res<ActivePaintTool>,
+    brush: Res<Brush>,
+    settings: Res<ScatterSettings>,
+    symmetry: Res<Symmetry>,
+    mut hex_map: ResMut<HexMap>,
+    mut history: ResMut<EditHistory>,
+    mut preview: ResMut<PreviewHexes>,
+    mut rng: Local<Option<SeededRng>>,
+    mut stroke_seed: Local<Option<u64>>,
+    // Hex under the cursor when the brush last scattered, while the button is held.
+    mut stroke_end: Local<Option<hgs::TileIndex>>,
+) {
+    if tool.0 != PaintTool::Scatter {
+        *stroke_end = None;
+        return;
+    }
+    let seed = *stroke_seed.get_or_insert_with(|| {
+        rng.get_or_insert_with(|| SeededRng::new(random_seed()))
+            .next_u64()
+    });
+
+    let orientation = hex_map.orientation();
+    match hovered.index {
+        Some(index) => {
+            if !alt_pressed(&keys) && buttons.pressed(MouseButton::Left) {
+                let from = stroke_end.unwrap_or(index);
+                let hexes = swept(from, index, brush.radius);
+                for (index, tag) in scattered(&settings, &symmetry, orientation, seed, hexes) {
+                    let data = paint_data(tag).map(|data| data.painted_over(hex_map.get(index)));
+                    history.apply(&mut hex_map, index, data);
+                }
+                *stroke_end = Some(index);
+            }
+            let footprint = hex::within(index, brush.radius as i64);
+            preview.set_if_neq(PreviewHexes(scattered(
+                &settings,
+                &symmetry,
+                orientation,
+                seed,
+                footprint,
+            )));
+        }
+        None => {
+            preview.set_if_neq(PreviewHexes::default());
+            *stroke_end = None;
+        }
+    }
+
+    if !buttons.pressed(MouseButton::Left) {
+        *stroke_end = None;
+    }
+    // The next stroke scatters differently. `paint_system`, which runs after this system,
+    // closes the undo step.
+    if buttons.just_released(MouseButton::Left) {
+        *stroke_seed = None;
+    }
+}
+
+/// Tiles a stroke with `seed` scatters over `hexes`. Symmetric counterparts get the tile
+/// of the hex they repeat, turned with it.
+fn scattered(
+    settings: &ScatterSettings,
+    symmetry: &Symmetry,
+    orientation: Orientation,
+    seed: u64,
+    hexes: impl IntoIterator<Item = hgs::TileIndex>,
+) -> HashMap<hgs::TileIndex, AssetTag> {
+    let mut picks = HashMap::new();
+    for index in hexes {
+        let Some(tag) = settings.pick(seed, index) else {
+            continue;
+        };
+        for (counterpart, turn) in symmetry.counterparts(index, orientation) {
+            picks.insert(counterpart, turn.tag(tag));
+        }
+    }
+    picks
+}
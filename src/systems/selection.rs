@@ -0,0 +1,349 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+use std::collections::HashSet;
+
+use crate::asset_loading::{AssetTag, Biome};
+use crate::hex;
+use crate::history::EditHistory;
+use crate::map::{HexData, HexMap};
+use crate::resources::{
+    ActivePaintTool, Brush, HoveredTile, PaintTool, PreviewHexes, SelectShape, SelectedHex,
+    Selection, SelectionSettings, TileImageHandles, WorldCoords,
+};
+use crate::systems::paint::{paint_data, swept};
+use crate::systems::tools::{alt_pressed, ctrl_pressed};
+use crate::{SELECTION_QUEUE, SELECTION_SNAPSHOT};
+
+/// Tint of selected tiles. Only the colour changes, so the hover pop-out (which scales and
+/// raises the tile) still shows on top of it.
+pub const SELECTED_TINT: Color = Color::srgb(0.55, 0.75, 1.0);
+
+/// World units the cursor moves before the lasso outline gets another point.
+const LASSO_STEP: f32 = 8.;
+
+/// Event for the selection, sent from the UI or the keyboard.
+#[derive(Event, Debug, Clone, Copy)]
+pub enum SelectionEvent {
+    SetShape(SelectShape),
+    Clear,
+    /// Paint every selected hex with the selected tile (or erase them with the eraser).
+    Repaint,
+    /// Remove the selected hexes from the map.
+    Erase,
+    /// Redraw the selected hexes for another biome, where the art exists.
+    SwapBiome(Biome),
+}
+
+/// A drag of the select tool in progress.
+pub struct SelectDrag {
+    /// Hex where the button was pressed.
+    start: hgs::TileIndex,
+    /// Shift+pressing on a selected hex deselects what the drag covers.
+    removing: bool,
+    /// Hex under the cursor on the previous frame, for sweeping the brush.
+    last_hex: Option<hgs::TileIndex>,
+    /// Cursor positions since the press: the rectangle's first corner, or the lasso outline.
+    points: Vec<Vec2>,
+}
+
+/// Drains selection requests from the JS queue.
+pub fn flush_selection_queue(mut writer: EventWriter<SelectionEvent>) {
+    if let Ok(mut queue) = SELECTION_QUEUE.lock() {
+        for event in queue.drain(..) {
+            writer.send(event);
+        }
+    }
+}
+
+/// Escape clears the selection and Delete erases the selected hexes.
+pub fn selection_keyboard_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut writer: EventWriter<SelectionEvent>,
+) {
+    if ctrl_pressed(&keys) {
+        return;
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        writer.send(SelectionEvent::Clear);
+    }
+    if keys.just_pressed(KeyCode::Delete) {
+        writer.send(SelectionEvent::Erase);
+    }
+}
+
+/// Handles the select tool: a click selects a hex, a drag selects the hexes its
+/// `SelectShape` covers. Without Shift the selection starts over; Shift adds to it, or
+/// removes from it when pressed on a selected hex, so Shift+click toggles a hex.
+/// Brush selections apply while sweeping, rectangles and lassos on release.
+#[allow(clippy::too_many_arguments)]
+pub fn select_system(
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    hovered: Res<HoveredTile>,
+    coords: Res<WorldCoords>,
+    tool: Res<ActivePaintTool>,
+    settings: Res<SelectionSettings>,
+    brush: Res<Brush>,
+    hex_map: Res<HexMap>,
+    mut selection: ResMut<Selection>,
+    mut preview: ResMut<PreviewHexes>,
+    mut drag: Local<Option<SelectDrag>>,
+) {
+    if tool.0 != PaintTool::Select {
+        *drag = None;
+        return;
+    }
+
+    if buttons.just_pressed(MouseButton::Left)
+        && !alt_pressed(&keys)
+        && let Some(index) = hovered.index
+    {
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        if !shift && !selection.0.is_empty() {
+            selection.0.clear();
+        }
+        *drag = Some(SelectDrag {
+            start: index,
+            removing: shift && selection.0.contains(&index),
+            last_hex: None,
+            points: vec![coords.0],
+        });
+    }
+
+    let Some(active) = drag.as_mut() else {
+        let footprint: PreviewHexes = match (hovered.index, settings.shape) {
+            (Some(index), SelectShape::Brush) => outlined(hex::within(index, brush.radius as i64)),
+            (Some(index), _) => outlined([index]),
+            (None, _) => PreviewHexes::default(),
+        };
+        preview.set_if_neq(footprint);
+        return;
+    };
+
+    let mut covered: HashSet<hgs::TileIndex> = match settings.shape {
+        SelectShape::Brush => {
+            if let Some(index) = hovered.index {
+                let from = active.last_hex.unwrap_or(index);
+                let hexes = swept(from, index, brush.radius);
+                update_selection(&mut selection, hexes, active.removing);
+            }
+            active.last_hex = hovered.index;
+            match hovered.index {
+                Some(index) => hex::within(index, brush.radius as i64).collect(),
+                None => HashSet::new(),
+            }
+        }
+        SelectShape::Rectangle => {
+            let bounds = Rect::from_corners(active.points[0], coords.0);
+            hexes_inside(&hex_map, bounds, |_| true)
+        }
+        SelectShape::Lasso => {
+            if active
+                .points
+                .last()
+                .is_none_or(|last| last.distance(coords.0) >= LASSO_STEP)
+            {
+                active.points.push(coords.0);
+            }
+            let bounds = active.points.iter().fold(
+                Rect::from_center_size(active.points[0], Vec2::ZERO),
+                |bounds, point| bounds.union_point(*point),
+            );
+            hexes_inside(&hex_map, bounds, |point| {
+                polygon_contains(&active.points, point)
+            })
+        }
+    };
+    // A click without leaving the pressed hex picks just that hex.
+    if hovered.index == Some(active.start) {
+        covered.insert(active.start);
+    }
+
+    if !buttons.pressed(MouseButton::Left) {
+        if settings.shape != SelectShape::Brush {
+            update_selection(&mut selection, covered, active.removing);
+        }
+        *drag = None;
+        preview.set_if_neq(PreviewHexes::default());
+        return;
+    }
+    preview.set_if_neq(outlined(covered));
+}
+
+fn update_selection(
+    selection: &mut Selection,
+    hexes: impl IntoIterator<Item = hgs::TileIndex>,
+    removing: bool,
+) {
+    for index in hexes {
+        if removing {
+            selection.0.remove(&index);
+        } else {
+            selection.0.insert(index);
+        }
+    }
+}
+
+/// Hexes marked with the outline tile, which previews a selection without painting anything.
+fn outlined(hexes: impl IntoIterator<Item = hgs::TileIndex>) -> PreviewHexes {
+    PreviewHexes(
+        hexes
+            .into_iter()
+            .map(|index| (index, AssetTag::Outline))
+            .collect(),
+    )
+}
+
+/// Hexes of the map whose centres lie in `bounds` and pass `inside`. Only chunks that
+/// overlap `bounds` are looked at.
+fn hexes_inside(
+    map: &HexMap,
+    bounds: Rect,
+    inside: impl Fn(Vec2) -> bool,
+) -> HashSet<hgs::TileIndex> {
+    let orientation = map.orientation();
+    map.chunk_coords()
+        .filter(|coord| !coord.bounds(orientation).intersect(bounds).is_empty())
+        .flat_map(|coord| map.chunk_tiles(coord))
+        .filter(|index| {
+            let centre = orientation.world_position(hex::cube(*index));
+            bounds.contains(centre) && inside(centre)
+        })
+        .collect()
+}
+
+/// Even-odd test of whether `point` lies inside the closed outline through `points`.
+fn polygon_contains(points: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Applies selection requests. Bulk edits of the selected hexes are one undo step each,
+/// unless a stroke is being painted, which they join. Repainting and swapping biomes only
+/// change hexes that exist in the map.
+pub fn handle_selection_event(
+    mut events: EventReader<SelectionEvent>,
+    mut selection: ResMut<Selection>,
+    mut settings: ResMut<SelectionSettings>,
+    selected_hex: Res<SelectedHex>,
+    tile_image_handles: Res<TileImageHandles>,
+    mut hex_map: ResMut<HexMap>,
+    mut history: ResMut<EditHistory>,
+) {
+    for event in events.read() {
+        let edits: Vec<(hgs::TileIndex, Option<HexData>)> = match *event {
+            SelectionEvent::SetShape(shape) => {
+                settings.shape = shape;
+                continue;
+            }
+            SelectionEvent::Clear => {
+                if !selection.0.is_empty() {
+                    selection.0.clear();
+                }
+                continue;
+            }
+            SelectionEvent::Repaint => {
+                let after = paint_data(selected_hex.0);
+                selection
+                    .0
+                    .iter()
+                    .filter_map(|&index| {
+                        let current = hex_map.get(index)?;
+                        Some((index, after.map(|data| data.painted_over(Some(current)))))
+                    })
+                    .collect()
+            }
+            SelectionEvent::Erase => selection.0.iter().map(|&index| (index, None)).collect(),
+            SelectionEvent::SwapBiome(biome) => selection
+                .0
+                .iter()
+                .filter_map(|&index| {
+                    let data = *hex_map.get(index)?;
+                    let tag = data
+                        .tag
+                        .with_biome(biome)
+                        .filter(|tag| tile_image_handles.handles.contains_key(tag))?;
+                    Some((index, Some(HexData { tag, ..data })))
+                })
+                .collect(),
+        };
+
+        let stroke_open = history.stroke_open();
+        for (index, data) in edits {
+            history.apply(&mut hex_map, index, data);
+        }
+        if !stroke_open {
+            history.end_stroke();
+        }
+    }
+}
+
+/// Marks hexes that were selected or deselected dirty, so the sync system redraws their
+/// chunks with or without the selection tint.
+pub fn selection_highlight_system(
+    selection: Res<Selection>,
+    mut hex_map: ResMut<HexMap>,
+    // The selection as last drawn.
+    mut highlighted: Local<HashSet<hgs::TileIndex>>,
+) {
+    if !selection.is_changed() {
+        return;
+    }
+    let changed: Vec<hgs::TileIndex> = highlighted
+        .symmetric_difference(&selection.0)
+        .copied()
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+    // Redrawing the tint is not an edit of the document.
+    hex_map.bypass_change_detection().mark_dirty(changed);
+    highlighted.clone_from(&selection.0);
+}
+
+/// Keeps the copy of the selection read by `export_selection` up to date.
+pub fn publish_selection_snapshot(selection: Res<Selection>) {
+    if !selection.is_changed() {
+        return;
+    }
+    if let Ok(mut snapshot) = SELECTION_SNAPSHOT.lock() {
+        snapshot.clone_from(&selection.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polygon_contains_points_inside_the_outline() {
+        // An L shape, so one corner of its bounding box is outside.
+        let outline = [
+            Vec2::new(0., 0.),
+            Vec2::new(4., 0.),
+            Vec2::new(4., 2.),
+            Vec2::new(2., 2.),
+            Vec2::new(2., 4.),
+            Vec2::new(0., 4.),
+        ];
+        assert!(polygon_contains(&outline, Vec2::new(1., 1.)));
+        assert!(polygon_contains(&outline, Vec2::new(3., 1.)));
+        assert!(polygon_contains(&outline, Vec2::new(1., 3.)));
+        assert!(!polygon_contains(&outline, Vec2::new(3., 3.)));
+        assert!(!polygon_contains(&outline, Vec2::new(-1., 1.)));
+    }
+
+    #[test]
+    fn degenerate_outlines_contain_nothing() {
+        assert!(!polygon_contains(&[], Vec2::ZERO));
+        assert!(!polygon_contains(&[Vec2::ZERO], Vec2::ZERO));
+    }
+}
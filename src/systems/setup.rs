@@ -1,20 +1,27 @@
 use bevy::asset::RenderAssetUsages;
-use bevy::color::palettes::css;
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
-use hexgridspiral as hgs;
 use std::collections::HashMap;
 
-use crate::asset_loading::{AssetTag, all_asset_tags, load_tag};
-use crate::components::{MainCamera, TileMarker};
-use crate::resources::{NUM_TILES, TileImageHandles};
-use crate::tile_config::{image_size, step_size};
-
-/// Bevy startup system: sets up the 2D camera, loads assets, and spawns all tiles.
+use crate::asset_loading::{AssetTag, TileCatalogHandle};
+use crate::autosave::offer_restore;
+use crate::components::{HoverMarker, MainCamera};
+use crate::map::HexMap;
+use crate::map_link;
+use crate::map_shape::MapShape;
+use crate::random::random_seed;
+use crate::resources::TileImageHandles;
+use crate::systems::map_shape::blank_tiles;
+use crate::tile_config::image_size;
+
+/// Bevy startup system: sets up the 2D camera, loads assets, and picks the initial map:
+/// a map shared through the URL, or else blank tiles while the page is offered the last
+/// autosaved session. The tiles themselves are drawn by the sync system.
 pub fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut images: ResMut<Assets<Image>>,
+    mut hex_map: ResMut<HexMap>,
 ) {
     // Spawn main camera with a blue-gray background color.
     commands.spawn((
@@ -26,38 +33,44 @@ pub fn setup(
         MainCamera,
     ));
 
-    // Load images for several asset tags.
-    let tags_to_load = all_asset_tags();
-
-    let transparent_img = create_transparent_image(&mut images);
-
-    let mut tag_to_handles: HashMap<AssetTag, Handle<Image>> = tags_to_load
-        .into_iter()
-        .map(|tag| (tag, load_tag(tag, &asset_server, transparent_img.clone())))
-        .collect();
+    // Drawn over the hovered tile by `hover_highlight_system`.
+    let (width, height) = image_size();
+    commands.spawn((
+        Sprite {
+            custom_size: Some(Vec2::new(width as f32, height as f32)),
+            ..default()
+        },
+        Visibility::Hidden,
+        HoverMarker,
+    ));
 
-    tag_to_handles.insert(AssetTag::None, transparent_img);
+    // Tile images are loaded once the catalog is available, see `apply_tile_catalog`.
+    commands.insert_resource(TileCatalogHandle(asset_server.load("tiles.catalog.json")));
 
-    let start_image =
-        get_image_handle(&tag_to_handles, &AssetTag::Blank).expect("Failed to load default image");
+    let transparent_img = create_transparent_image(&mut images);
 
     commands.insert_resource(TileImageHandles {
-        handles: tag_to_handles,
+        handles: HashMap::from([(AssetTag::None, vec![transparent_img])]),
+        facings: HashMap::new(),
     });
 
-    let (step_x, step_y) = step_size();
-    let (image_w, image_h) = image_size();
-
-    // Spawn tiles
-    for tile_index in 0..NUM_TILES {
-        spawn_tile_with_index(
-            &mut commands,
-            &hgs::TileIndex::from(tile_index),
-            (image_w, image_h),
-            (step_x, step_y),
-            start_image.clone(),
-        );
+    match map_link::read_from_location() {
+        Some(Ok(document)) => {
+            hex_map.set_seed(document.seed);
+            hex_map.set_orientation(document.orientation);
+            hex_map.replace_all(document.tiles());
+            return;
+        }
+        Some(Err(err)) => log::warn!("Ignoring map link: {}", err),
+        None => {}
     }
+
+    offer_restore();
+
+    // Start with a blank map
+    hex_map.set_seed(random_seed());
+    let orientation = hex_map.orientation();
+    hex_map.replace_all(blank_tiles(MapShape::default(), orientation));
 }
 
 fn create_transparent_image(images: &mut Assets<Image>) -> Handle<Image> {
@@ -76,63 +89,3 @@ fn create_transparent_image(images: &mut Assets<Image>) -> Handle<Image> {
     );
     images.add(image)
 }
-
-/// Extracts an image handle from the asset map for a given tag and index.
-fn get_image_handle(
-    tag_to_handles: &HashMap<AssetTag, Handle<Image>>,
-    asset_tag: &AssetTag,
-) -> Option<Handle<Image>> {
-    tag_to_handles.get(asset_tag).cloned()
-}
-
-/// Spawns a tile entity (sprite + text) at a given hex grid location.
-pub fn spawn_tile_with_index(
-    commands: &mut Commands,
-    tile_index: &hgs::TileIndex,
-    image_size: (f64, f64),
-    step_size: (f64, f64),
-    start_image: Handle<Image>,
-) {
-    let t = hgs::HGSTile::new(*tile_index)
-        .cc()
-        .to_irregular_pixel((0., 0.), step_size);
-    let (x, y, z) = (t.0 as f32, t.1 as f32, (t.0 as f32) * -0.0001);
-
-    // Rotate position 90° CCW around origin (0, 0)
-    let rotated_position = Vec3::new(-y, x, z);
-    let rotation = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
-    let child_rotation = rotation.inverse();
-
-    // Create hexagonal tile with a text as child node
-    let mut tile_node = commands.spawn((
-        Sprite {
-            image: start_image.clone(),
-            custom_size: Some(Vec2::new(image_size.0 as f32, image_size.1 as f32)),
-            image_mode: SpriteImageMode::Auto,
-            ..default()
-        },
-        Transform {
-            translation: rotated_position,
-            rotation,
-            ..default()
-        },
-        TileMarker {
-            index: *tile_index,
-            tag: AssetTag::Blank,
-        },
-    ));
-
-    tile_node.with_children(|parent| {
-        // Tile Index text node
-        parent.spawn((
-            Text2d::new(format!("{}", tile_index)),
-            TextColor(css::DIM_GRAY.into()),
-            // avoid z-fighting. The child transform is relative to the parent.
-            Transform {
-                translation: Vec3::new(0., 0., 0.0001),
-                rotation: child_rotation,
-                ..default()
-            },
-        ));
-    });
-}
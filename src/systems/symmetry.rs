@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use hexgridspiral as hgs;
+
+use crate::{
+    SYMMETRY_QUEUE, resources::HoveredTile, symmetry::Symmetry, systems::tools::ctrl_pressed,
+};
+
+/// Event changing symmetric painting, sent from the UI or the keyboard.
+#[derive(Event, Debug, Clone, Copy)]
+pub enum SymmetryEvent {
+    /// Which mirrors and how many rotated copies to paint; see `Symmetry`.
+    Set {
+        mirror_horizontal: bool,
+        mirror_vertical: bool,
+        rotations: u8,
+    },
+    /// Move the centre to a hex, or back to the origin with `None`.
+    SetCentre(Option<hgs::TileIndex>),
+}
+
+/// Drains symmetry changes from the JS queue.
+pub fn flush_symmetry_queue(mut writer: EventWriter<SymmetryEvent>) {
+    if let Ok(mut queue) = SYMMETRY_QUEUE.lock() {
+        for event in queue.drain(..) {
+            writer.send(event);
+        }
+    }
+}
+
+/// `C` moves the symmetry centre to the hovered hex.
+pub fn symmetry_keyboard_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    hovered: Res<HoveredTile>,
+    mut writer: EventWriter<SymmetryEvent>,
+) {
+    if ctrl_pressed(&keys) || !keys.just_pressed(KeyCode::KeyC) {
+        return;
+    }
+    if let Some(index) = hovered.index {
+        writer.send(SymmetryEvent::SetCentre(Some(index)));
+    }
+}
+
+pub fn handle_symmetry_event(
+    mut events: EventReader<SymmetryEvent>,
+    mut symmetry: ResMut<Symmetry>,
+) {
+    for event in events.read() {
+        match *event {
+            SymmetryEvent::Set {
+                mirror_horizontal,
+                mirror_vertical,
+                rotations,
+            } => {
+                symmetry.mirror_horizontal = mirror_horizontal;
+                symmetry.mirror_vertical = mirror_vertical;
+                symmetry.rotations = rotations;
+            }
+            SymmetryEvent::SetCentre(centre) => {
+                symmetry.centre = centre.unwrap_or_else(|| Symmetry::default().centre);
+            }
+        }
+        debug!("Symmetry set to {:?}", *symmetry);
+    }
+}
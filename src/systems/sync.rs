@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use bevy::sprite::AlphaMode2d;
+use std::collections::{HashMap, HashSet};
+
+use crate::chunks::{ChunkCoord, TileQuad, tile_mesh};
+use crate::hex;
+use crate::map::HexMap;
+use crate::resources::{ChunkMeshes, Selection, TileImageHandles};
+use crate::systems::chunks::VisibleChunks;
+use crate::systems::selection::SELECTED_TINT;
+
+/// Sent when the sync system rebuilds a chunk in view, e.g. so its labels are redone.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ChunkChangedEvent(pub ChunkCoord);
+
+/// Brings the chunk meshes in line with the `HexMap` document.
+///
+/// Every chunk with a hex marked dirty since the last run is rebuilt: its entity is
+/// despawned and, if the chunk is in view, spawned again with one mesh per tile image.
+/// Chunks out of view are left without an entity.
+#[allow(clippy::too_many_arguments)]
+pub fn sync_tiles_system(
+    mut commands: Commands,
+    mut hex_map: ResMut<HexMap>,
+    tile_image_handles: Res<TileImageHandles>,
+    selection: Res<Selection>,
+    visible_chunks: Res<VisibleChunks>,
+    mut chunk_meshes: ResMut<ChunkMeshes>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut changed: EventWriter<ChunkChangedEvent>,
+    // One material per tile image, shared by every chunk drawing it.
+    mut tile_materials: Local<HashMap<Handle<Image>, Handle<ColorMaterial>>>,
+) {
+    // Draining the dirty set is bookkeeping, not an edit of the document.
+    let dirty = hex_map.bypass_change_detection().take_dirty();
+    if dirty.is_empty() {
+        return;
+    }
+    let chunks: HashSet<ChunkCoord> = dirty.into_iter().map(ChunkCoord::of).collect();
+
+    let seed = hex_map.seed();
+    let orientation = hex_map.orientation();
+    for coord in chunks {
+        if let Some(entity) = chunk_meshes.0.remove(&coord) {
+            commands.entity(entity).despawn_recursive();
+        }
+        if !visible_chunks.0.contains(&coord) {
+            continue;
+        }
+        changed.send(ChunkChangedEvent(coord));
+
+        let mut quads: HashMap<Handle<Image>, Vec<TileQuad>> = HashMap::new();
+        for index in hex_map.chunk_tiles(coord) {
+            let Some(data) = hex_map.get(index) else {
+                continue;
+            };
+            let facing = tile_image_handles.facing(data.tag);
+            let color = if selection.0.contains(&index) {
+                SELECTED_TINT
+            } else {
+                Color::WHITE
+            };
+            quads
+                .entry(tile_image_handles.for_hex(seed, index, data))
+                .or_default()
+                .push(TileQuad {
+                    position: orientation.world_position(hex::cube(index)),
+                    rotation: orientation.tile_rotation(facing),
+                    flip_x: facing.flip_x,
+                    color,
+                });
+        }
+        if quads.is_empty() {
+            continue;
+        }
+
+        let entity = commands
+            .spawn((Transform::default(), Visibility::default()))
+            .with_children(|parent| {
+                for (image, quads) in quads {
+                    let material = tile_materials
+                        .entry(image.clone())
+                        .or_insert_with(|| {
+                            materials.add(ColorMaterial {
+                                texture: Some(image),
+                                // Masked rather than blended, so tiles of different meshes
+                                // overlap by depth like the tiles within one mesh.
+                                alpha_mode: AlphaMode2d::Mask(0.5),
+                                ..default()
+                            })
+                        })
+                        .clone();
+                    parent.spawn((
+                        Mesh2d(meshes.add(tile_mesh(&quads))),
+                        MeshMaterial2d(material),
+                    ));
+                }
+            })
+            .id();
+        chunk_meshes.0.insert(coord, entity);
+    }
+}
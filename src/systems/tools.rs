@@ -1,9 +1,18 @@
-use crate::{TOOL_QUEUE, asset_loading::AssetTag, resources::SelectedHex};
+use crate::{
+    BRUSH_QUEUE, FILL_LIMIT_QUEUE, PAINT_TOOL_QUEUE, TOOL_QUEUE,
+    asset_loading::AssetTag,
+    js_events,
+    map::HexMap,
+    resources::{
+        ActivePaintTool, Brush, FillSettings, HoveredTile, MAX_BRUSH_RADIUS, PaintTool, SelectedHex,
+    },
+};
 use bevy::prelude::*;
+use wasm_bindgen::JsValue;
 
 /// Event sent when the user selects a new tool from the UI.
 #[derive(Event)]
-pub struct HexSelectedEvent(pub String);
+pub struct HexSelectedEvent(pub AssetTag);
 
 /// Updates the selected tool in response to a `ToolSelectedEvent`.
 pub fn on_hex_selected(
@@ -11,8 +20,50 @@ pub fn on_hex_selected(
     mut selected: ResMut<SelectedHex>,
 ) {
     for event in events.read() {
-        // log::warn!("Tool selected via event: {}", event.0);
-        selected.0 = AssetTag::from_str(&event.0);
+        // log::warn!("Tool selected via event: {:?}", event.0);
+        selected.0 = event.0;
+    }
+}
+
+/// Whether Alt is held, which turns a left click into an eyedropper pick.
+pub fn alt_pressed(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+}
+
+/// Whether Ctrl (or Cmd on macOS) is held, which turns letter keys into shortcuts.
+pub fn ctrl_pressed(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ])
+}
+
+/// Right-click or Alt+click on a hex selects its tile. Empty hexes are ignored.
+pub fn eyedropper_system(
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    hovered: Res<HoveredTile>,
+    hex_map: Res<HexMap>,
+    mut selected: ResMut<SelectedHex>,
+) {
+    let picked = buttons.just_pressed(MouseButton::Right)
+        || (alt_pressed(&keys) && buttons.just_pressed(MouseButton::Left));
+    if !picked {
+        return;
+    }
+    if let Some(data) = hovered.index.and_then(|index| hex_map.get(index)) {
+        selected.set_if_neq(SelectedHex(data.tag));
+    }
+}
+
+/// Tells the toolbox which tile is selected, whether it was picked in the palette or with
+/// the eyedropper. Fires `tileselected` with the tile id.
+pub fn notify_tile_selected(selected: Res<SelectedHex>) {
+    if selected.is_changed() {
+        let id: &'static str = selected.0.into();
+        js_events::dispatch("tileselected", &JsValue::from_str(id));
     }
 }
 
@@ -24,3 +75,116 @@ pub fn flush_click_events_system(mut writer: EventWriter<HexSelectedEvent>) {
         }
     }
 }
+
+/// Event sent when the brush radius is changed from the UI.
+#[derive(Event)]
+pub struct SetBrushRadiusEvent(pub u8);
+
+/// Drains brush radius changes from the JS queue and injects them into Bevy’s system.
+pub fn flush_brush_queue(mut writer: EventWriter<SetBrushRadiusEvent>) {
+    if let Ok(mut queue) = BRUSH_QUEUE.lock() {
+        for radius in queue.drain(..) {
+            writer.send(SetBrushRadiusEvent(radius));
+        }
+    }
+}
+
+/// `[` and `]` shrink and grow the brush.
+pub fn brush_keyboard_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    brush: Res<Brush>,
+    mut writer: EventWriter<SetBrushRadiusEvent>,
+) {
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        writer.send(SetBrushRadiusEvent(brush.radius.saturating_sub(1)));
+    }
+    if keys.just_pressed(KeyCode::BracketRight) {
+        writer.send(SetBrushRadiusEvent(brush.radius + 1));
+    }
+}
+
+/// Updates the brush radius and tells the toolbox, which fires `brushradiuschanged`.
+pub fn on_brush_radius_set(mut events: EventReader<SetBrushRadiusEvent>, mut brush: ResMut<Brush>) {
+    for event in events.read() {
+        let radius = event.0.min(MAX_BRUSH_RADIUS);
+        if brush.radius != radius {
+            brush.radius = radius;
+            js_events::dispatch("brushradiuschanged", &JsValue::from(radius));
+        }
+    }
+}
+
+/// Event sent when the paint tool is changed from the UI or the keyboard.
+#[derive(Event)]
+pub struct SetPaintToolEvent(pub PaintTool);
+
+/// Event sent when the fill limit is changed from the UI.
+#[derive(Event)]
+pub struct SetFillLimitEvent(pub Option<u32>);
+
+/// Drains paint tool and fill limit changes from the JS queues.
+pub fn flush_paint_tool_queue(
+    mut tool_writer: EventWriter<SetPaintToolEvent>,
+    mut fill_writer: EventWriter<SetFillLimitEvent>,
+) {
+    if let Ok(mut queue) = PAINT_TOOL_QUEUE.lock() {
+        for tool in queue.drain(..) {
+            tool_writer.send(SetPaintToolEvent(tool));
+        }
+    }
+    if let Ok(mut queue) = FILL_LIMIT_QUEUE.lock() {
+        for limit in queue.drain(..) {
+            fill_writer.send(SetFillLimitEvent(limit));
+        }
+    }
+}
+
+/// `B` picks the brush and `G` the fill tool.
+pub fn paint_tool_keyboard_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut writer: EventWriter<SetPaintToolEvent>,
+) {
+    if ctrl_pressed(&keys) {
+        return;
+    }
+    if keys.just_pressed(KeyCode::KeyB) {
+        writer.send(SetPaintToolEvent(PaintTool::Brush));
+    }
+    if keys.just_pressed(KeyCode::KeyG) {
+        writer.send(SetPaintToolEvent(PaintTool::Fill));
+    }
+    if keys.just_pressed(KeyCode::KeyL) {
+        writer.send(SetPaintToolEvent(PaintTool::Line));
+    }
+    if keys.just_pressed(KeyCode::KeyR) {
+        writer.send(SetPaintToolEvent(PaintTool::Ring));
+    }
+    if keys.just_pressed(KeyCode::KeyH) {
+        writer.send(SetPaintToolEvent(PaintTool::Hexagon));
+    }
+    if keys.just_pressed(KeyCode::KeyM) {
+        writer.send(SetPaintToolEvent(PaintTool::Select));
+    }
+    if keys.just_pressed(KeyCode::KeyT) {
+        writer.send(SetPaintToolEvent(PaintTool::Scatter));
+    }
+}
+
+/// Switches the paint tool and tells the toolbox, which fires `painttoolchanged`.
+pub fn on_paint_tool_set(
+    mut tool_events: EventReader<SetPaintToolEvent>,
+    mut fill_events: EventReader<SetFillLimitEvent>,
+    mut active: ResMut<ActivePaintTool>,
+    mut fill: ResMut<FillSettings>,
+) {
+    for event in tool_events.read() {
+        if active.0 != event.0 {
+            active.0 = event.0;
+            let name: &'static str = event.0.into();
+            js_events::dispatch("painttoolchanged", &JsValue::from_str(name));
+        }
+    }
+    for event in fill_events.read() {
+        fill.max_distance = event.0;
+    }
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+use crate::{
+    history::EditHistory,
+    map::{HexData, HexMap},
+    resources::{HoveredTile, TileImageHandles},
+    systems::tools::ctrl_pressed,
+};
+
+/// `V` cycles the art variant of the hovered hex and locks it there;
+/// `Shift+V` unlocks it so the variant is picked from the map seed again.
+pub fn variant_keyboard_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    hovered: Res<HoveredTile>,
+    tile_image_handles: Res<TileImageHandles>,
+    mut hex_map: ResMut<HexMap>,
+    mut history: ResMut<EditHistory>,
+) {
+    // Ctrl+V pastes instead.
+    if !keys.just_pressed(KeyCode::KeyV) || ctrl_pressed(&keys) {
+        return;
+    }
+    let Some(index) = hovered.index else {
+        return;
+    };
+    let Some(&data) = hex_map.get(index) else {
+        return;
+    };
+
+    let unlock = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let variant = if unlock {
+        None
+    } else {
+        let count = tile_image_handles.variant_count(data.tag);
+        if count < 2 {
+            return;
+        }
+        let current = data.variant_index(hex_map.seed(), index, count);
+        Some(((current + 1) % count) as u8)
+    };
+
+    history.apply(&mut hex_map, index, Some(HexData { variant, ..data }));
+    history.end_stroke();
+}
@@ -1,8 +1,60 @@
 
-import init, { set_tile, set_show_tile_labels } from "./pkg/double_hexxed.js"
+import init, {
+    set_tile,
+    list_tiles,
+    set_show_tile_labels,
+    set_label_mode,
+    set_brush_radius,
+    set_paint_tool,
+    set_fill_limit,
+    set_scatter_palette,
+    set_scatter_density,
+    set_symmetry,
+    reset_symmetry_centre,
+    set_select_shape,
+    clear_selection,
+    repaint_selection,
+    erase_selection,
+    swap_selection_biome,
+    export_selection,
+    copy_selection,
+    rotate_clipboard,
+    mirror_clipboard,
+    save_stamp,
+    use_stamp,
+    delete_stamp,
+    list_stamps,
+    set_auto_coast,
+    export_map,
+    import_map,
+    list_autosaves,
+    restore_autosave,
+    share_map_link,
+    load_map_link,
+    undo,
+    redo,
+    new_map,
+    resize_map,
+    set_orientation
+} from "./pkg/double_hexxed.js"
 run();
 
 async function run() {
+    // Registered before the app starts, as it looks for an autosave on startup.
+    // Rust dispatches events synchronously, so the prompt waits until the app is running.
+    window.addEventListener("autosaveavailable", (event) => {
+        const { slot, saved_at, tiles } = event.detail;
+        setTimeout(() => {
+            const savedAt = new Date(saved_at).toLocaleString();
+            if (confirm(`Restore your last session (${tiles} tiles, saved ${savedAt})?`)) {
+                restore_autosave(slot);
+            }
+        });
+    }, { once: true });
+    window.addEventListener("autosavestopped", (event) => {
+        setTimeout(() => alert(`Autosave has stopped: ${event.detail}. Save the map to a file to keep it.`));
+    });
+
     try {
         await init();
     } catch (error) {
@@ -17,108 +69,317 @@ async function run() {
     }
     
     // Custom UI logic
-    // TILES must match asset_loading "AssetTag"
-    const TILES_BY_GROUP = {
-        "Simple": [
-            "Delete",
-            "Outline",
-            "Blank (white)",
-            "Lush (green)",
-            "Ocean (blue)",
-            "Rocky (gray)",
-            "Snowy (off-white)"
-        ],
-        "Forests": [
-            "Sparse Trees, Lush",
-            "Sparse Trees, Snowy",
-            "Forest Conifer, Lush",
-            "Forest Conifer, Snowy",
-            "Forest Deciduous, Lush",
-            "Forest Mixed, Lush"
-        ],
-        "Hills": [
-            "Hills, Desert",
-            "Hills, Lush",
-            "Hills, Snowy",
-            "Mountain Foothills, Lush",
-            "Mountain Foothills, Rocky",
-            "Mountain Foothills, Snowy"
-        ],
-        "Mountains": [
-            "Mountain Volcano, Lush",
-            "Mountain Volcano, Rocky",
-            "Mountain Volcano, Snowy",
-            "Mountain Low, Lush",
-            "Mountain Low, Rocky",
-            "Mountain Low, Snowy",
-            "Mountain Medium, Lush",
-            "Mountain Medium, Rocky",
-            "Mountain Medium, Snowy",
-            "Mountain Peak, Lush",
-            "Mountain Peak, Rocky",
-            "Mountain Peak, Snowy"
-        ],
-        "Plains": [
-            "Plains, Damp",
-            "Plains, Desert",
-            "Plains, Farmland",
-            "Plains, Lush"
-        ],
-        "Snow": [
-            "Snow area",
-            "Snow drifts",
-            "Snow field"
-        ],
-        "Urban": [
-            "Ruins, Desert",
-            "Ruins, Lush",
-            "Urban City, Lush",
-            "Urban Farm, Lush",
-            "Urban Farmland, Lush",
-            "Urban Town, Abandoned",
-            "Urban Town, Inhabited",
-            "Urban Town Lumberyard",
-            "Urban Monastery",
-            "Urban Tower",
-            "Urban Town, Lush"
-        ],
-        "Water": [
-            "Ocean Soft Waves",
-            "Ocean Still Water",
-            "Ocean Waves",
-            "Wetlands, Damp"
-        ]
+    // The palette comes from the Rust tile catalog, which may still be loading.
+    const palette = list_tiles();
+    if (palette) {
+        buildPalette(palette);
+    } else {
+        window.addEventListener("tilecatalogloaded", () => buildPalette(list_tiles()), { once: true });
+    }
+
+    const checkbox = document.getElementById("toggle-labels-checkbox");
+    checkbox.addEventListener("change", () => {
+        set_show_tile_labels(checkbox.checked);
+    });
+
+    const labelModeSelect = document.getElementById("label-mode-select");
+    labelModeSelect.addEventListener("change", () => set_label_mode(labelModeSelect.value));
+
+    const autoCoastCheckbox = document.getElementById("auto-coast-checkbox");
+    autoCoastCheckbox.addEventListener("change", () => {
+        set_auto_coast(autoCoastCheckbox.checked);
+    });
+
+    // The selected tile is highlighted in the palette, including picks made with the
+    // eyedropper (right-click or Alt+click on the map).
+    window.addEventListener("tileselected", (event) => highlightTile(event.detail));
+    // Right-click is the eyedropper, not the browser's context menu.
+    document.addEventListener("contextmenu", (event) => {
+        if (event.target instanceof HTMLCanvasElement) {
+            event.preventDefault();
+        }
+    });
+
+    // Paint tools, which can also be picked with B, G, L, R, H, T, M and Ctrl+V on the map
+    const paintToolButtons = document.querySelectorAll(".paint-tool-button");
+    const highlightPaintTool = (tool) => {
+        paintToolButtons.forEach((button) => {
+            button.classList.toggle("active", button.dataset.paintTool === tool);
+        });
+    };
+    paintToolButtons.forEach((button) => {
+        button.addEventListener("click", () => {
+            set_paint_tool(button.dataset.paintTool);
+            highlightPaintTool(button.dataset.paintTool);
+        });
+    });
+    window.addEventListener("painttoolchanged", (event) => highlightPaintTool(event.detail));
+
+    const fillLimit = document.getElementById("fill-limit");
+    fillLimit.addEventListener("change", () => {
+        set_fill_limit(fillLimit.value === "" ? undefined : Number(fillLimit.value));
+    });
+
+    // Brush size, which can also be changed with [ and ] on the map
+    const brushRadius = document.getElementById("brush-radius");
+    const brushRadiusValue = document.getElementById("brush-radius-value");
+    brushRadius.addEventListener("input", () => {
+        brushRadiusValue.textContent = brushRadius.value;
+        set_brush_radius(Number(brushRadius.value));
+    });
+    window.addEventListener("brushradiuschanged", (event) => {
+        brushRadius.value = event.detail;
+        brushRadiusValue.textContent = event.detail;
+    });
+
+    // Scatter brush palette, written as "TileId:weight, TileId:weight"
+    const scatterPalette = document.getElementById("scatter-palette");
+    scatterPalette.addEventListener("change", () => {
+        const entries = scatterPalette.value
+            .split(",")
+            .map((entry) => entry.trim())
+            .filter((entry) => entry !== "")
+            .map((entry) => {
+                const [id, weight = "1"] = entry.split(":").map((part) => part.trim());
+                return { id, weight: Number(weight) };
+            });
+        try {
+            set_scatter_palette(entries);
+        } catch (error) {
+            alert(`Could not set scatter palette: ${error}`);
+        }
+    });
+    const scatterDensity = document.getElementById("scatter-density");
+    const scatterDensityValue = document.getElementById("scatter-density-value");
+    scatterDensity.addEventListener("input", () => {
+        scatterDensityValue.textContent = scatterDensity.value;
+        set_scatter_density(Number(scatterDensity.value));
+    });
+
+    // Symmetric painting; C on the map moves the centre to the hovered hex
+    const mirrorHorizontal = document.getElementById("mirror-horizontal");
+    const mirrorVertical = document.getElementById("mirror-vertical");
+    const symmetryRotations = document.getElementById("symmetry-rotations");
+    const updateSymmetry = () => {
+        set_symmetry(mirrorHorizontal.checked, mirrorVertical.checked, Number(symmetryRotations.value));
+    };
+    [mirrorHorizontal, mirrorVertical, symmetryRotations].forEach((control) => {
+        control.addEventListener("change", updateSymmetry);
+    });
+    document.getElementById("reset-symmetry-centre").addEventListener("click", () => reset_symmetry_centre());
+
+    // Selection and bulk actions on it
+    const selectShape = document.getElementById("select-shape-select");
+    selectShape.addEventListener("change", () => set_select_shape(selectShape.value));
+    document.getElementById("repaint-selection").addEventListener("click", () => repaint_selection());
+    document.getElementById("erase-selection").addEventListener("click", () => erase_selection());
+    document.getElementById("clear-selection").addEventListener("click", () => clear_selection());
+    const swapBiome = document.getElementById("swap-biome-select");
+    swapBiome.addEventListener("change", () => {
+        if (swapBiome.value !== "") {
+            swap_selection_biome(swapBiome.value);
+        }
+        swapBiome.value = "";
+    });
+    document.getElementById("export-selection").addEventListener("click", () => {
+        const blob = new Blob([export_selection()], { type: "application/json" });
+        const link = document.createElement("a");
+        link.href = URL.createObjectURL(blob);
+        link.download = "selection.json";
+        link.click();
+        URL.revokeObjectURL(link.href);
+    });
+
+    // Copy / paste and stamps
+    document.getElementById("copy-selection").addEventListener("click", () => copy_selection());
+    document.getElementById("rotate-ccw").addEventListener("click", () => rotate_clipboard(1));
+    document.getElementById("rotate-cw").addEventListener("click", () => rotate_clipboard(-1));
+    document.getElementById("mirror").addEventListener("click", () => mirror_clipboard());
+    const stampName = document.getElementById("stamp-name");
+    const stampSelect = document.getElementById("stamp-select");
+    const fillStamps = (names) => {
+        stampSelect.length = 1;
+        names.forEach((name) => {
+            const option = document.createElement("option");
+            option.value = name;
+            option.textContent = name;
+            stampSelect.appendChild(option);
+        });
+    };
+    fillStamps(list_stamps());
+    window.addEventListener("stampschanged", (event) => fillStamps(event.detail));
+    document.getElementById("save-stamp").addEventListener("click", () => {
+        try {
+            save_stamp(stampName.value);
+        } catch (error) {
+            alert(`Could not save stamp: ${error}`);
+        }
+    });
+    stampSelect.addEventListener("change", () => {
+        if (stampSelect.value !== "") {
+            use_stamp(stampSelect.value);
+        }
+    });
+    document.getElementById("delete-stamp").addEventListener("click", () => {
+        if (stampSelect.value !== "" && confirm(`Delete the stamp "${stampSelect.value}"?`)) {
+            delete_stamp(stampSelect.value);
+        }
+    });
+
+    // Undo / redo
+    document.getElementById("undo").addEventListener("click", () => undo());
+    document.getElementById("redo").addEventListener("click", () => redo());
+
+    // New map / resize
+    const shapeSelect = document.getElementById("map-shape-select");
+    const widthInput = document.getElementById("map-width");
+    const heightInput = document.getElementById("map-height");
+    const readShape = () => {
+        const width = Number(widthInput.value);
+        const height = Number(heightInput.value);
+        return shapeSelect.value === "hexagon"
+            ? { shape: "hexagon", radius: width }
+            : { shape: shapeSelect.value, width, height };
     };
+    shapeSelect.addEventListener("change", () => {
+        heightInput.hidden = shapeSelect.value === "hexagon";
+    });
+    heightInput.hidden = shapeSelect.value === "hexagon";
+    document.getElementById("new-map").addEventListener("click", () => {
+        if (!window.confirm("Start a new map? The current one can still be restored with Undo.")) {
+            return;
+        }
+        try {
+            new_map(readShape());
+        } catch (error) {
+            alert(`Could not create map: ${error}`);
+        }
+    });
+    document.getElementById("resize-map").addEventListener("click", () => {
+        try {
+            resize_map(readShape());
+        } catch (error) {
+            alert(`Could not resize map: ${error}`);
+        }
+    });
+
+    const orientationSelect = document.getElementById("orientation-select");
+    orientationSelect.addEventListener("change", () => set_orientation(orientationSelect.value));
+
+    // Map save / load
+    document.getElementById("save-map").addEventListener("click", () => {
+        const blob = new Blob([export_map()], { type: "application/json" });
+        const link = document.createElement("a");
+        link.href = URL.createObjectURL(blob);
+        link.download = "map.json";
+        link.click();
+        URL.revokeObjectURL(link.href);
+    });
+
+    const loadInput = document.getElementById("load-map-input");
+    document.getElementById("load-map").addEventListener("click", () => loadInput.click());
+    loadInput.addEventListener("change", async () => {
+        const file = loadInput.files[0];
+        if (!file) {
+            return;
+        }
+        try {
+            import_map(await file.text());
+        } catch (error) {
+            alert(`Could not load map: ${error}`);
+        }
+        loadInput.value = "";
+    });
+
+    // Shareable links: the whole map lives in the URL fragment
+    const copyLinkButton = document.getElementById("copy-link");
+    copyLinkButton.addEventListener("click", async () => {
+        const link = share_map_link();
+        try {
+            await navigator.clipboard.writeText(link);
+            copyLinkButton.textContent = "Link copied!";
+        } catch (error) {
+            prompt("Copy this link:", link);
+        }
+        setTimeout(() => (copyLinkButton.textContent = "Copy link"), 1500);
+    });
+    window.addEventListener("hashchange", () => {
+        if (location.hash.startsWith("#map=")) {
+            load_map_link(location.hash);
+        }
+    });
+
+    // Autosave snapshots, refreshed every time the list is opened
+    const autosaveSelect = document.getElementById("autosave-select");
+    autosaveSelect.addEventListener("focus", () => {
+        autosaveSelect.length = 1;
+        JSON.parse(list_autosaves()).forEach(({ slot, saved_at, tiles }) => {
+            const option = document.createElement("option");
+            option.value = slot;
+            option.textContent = `${new Date(saved_at).toLocaleString()} (${tiles} tiles)`;
+            autosaveSelect.appendChild(option);
+        });
+    });
+    autosaveSelect.addEventListener("change", () => {
+        if (autosaveSelect.value !== "" && confirm("Replace the current map with this snapshot?")) {
+            restore_autosave(Number(autosaveSelect.value));
+        }
+        autosaveSelect.value = "";
+        autosaveSelect.blur();
+    });
+}
+
+let selectedTileId = null;
+
+function highlightTile(tileId) {
+    selectedTileId = tileId;
+    document.querySelectorAll(".tile-button").forEach((button) => {
+        const selected = button.dataset.tileId === tileId;
+        button.classList.toggle("active", selected);
+        if (selected && !button.hidden) {
+            button.scrollIntoView({ block: "nearest" });
+        }
+    });
+}
+
+function buildPalette(groups) {
     const toolbox = document.getElementById("toolbox");
-    
-    Object.entries(TILES_BY_GROUP).forEach(([groupName, tiles]) => {
+
+    groups.forEach(({ name: groupName, tiles }) => {
         const group = document.createElement("div");
         group.className = "tile-group";
-                
+
         const header = document.createElement("h4");
         header.textContent = `${groupName} ▸`;
         header.onclick = () => toggleGroup(header);
         group.appendChild(header);
-                
+
         const tileList = document.createElement("div");
         tileList.className = "tile-list";
-                
-        tiles.forEach(tileName => {
+
+        tiles.forEach(tile => {
             const button = document.createElement("button");
             button.className = "tile-button";
-            button.textContent = tileName;
+            button.dataset.tileId = tile.id;
+            if (tile.thumbnail) {
+                const thumbnail = document.createElement("img");
+                thumbnail.src = tile.thumbnail;
+                thumbnail.alt = "";
+                button.appendChild(thumbnail);
+            }
+            button.appendChild(document.createTextNode(tile.name));
             button.addEventListener("click", () => {
-                set_tile(tileName);
+                set_tile(tile.id);
             });
             tileList.appendChild(button);
         });
-    
+
         group.appendChild(tileList);
         toolbox.appendChild(group);
     });
-    
-    const checkbox = document.getElementById("toggle-labels-checkbox");
-    checkbox.addEventListener("change", () => {
-        set_show_tile_labels(checkbox.checked);
-    });
-}
\ No newline at end of file
+
+    // The initial selection may have been announced before the palette existed.
+    if (selectedTileId) {
+        highlightTile(selectedTileId);
+    }
+}